@@ -1,7 +1,11 @@
 use std::io::Read;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use mon_fs_box::{file_pc::FilePc, mon_field::ByteCount, pc::PC};
+use mon_fs_box::{
+    file_pc::{FilePc, PcEncodeOptions},
+    mon_field::ByteCount,
+    pc::PC,
+};
 
 fn write_and_pc(data: Vec<u8>) -> PC {
     let mut file_pc = FilePc::new();
@@ -16,10 +20,68 @@ fn read_entire_pc(mut pc: PC) {
     pc.read_to_end(&mut data).unwrap();
 }
 
+// Typical things to store, the start of each source file stands in for small text files
+fn workloads() -> Vec<(&'static str, FilePc)> {
+    let sources = [
+        include_str!("../src/pc.rs"),
+        include_str!("../src/file_pc.rs"),
+        include_str!("../src/pc_set.rs"),
+        include_str!("../src/plan.rs"),
+        include_str!("../src/reuse.rs"),
+        include_str!("../src/codec.rs"),
+        include_str!("../src/storage.rs"),
+        include_str!("../src/save.rs"),
+    ];
+
+    let mut small_text = FilePc::new();
+    for (i, source) in sources.iter().enumerate() {
+        let data = source.as_bytes()[..400].to_vec();
+        small_text
+            .add_file_raw(&format!("src/{}.rs", i), data)
+            .unwrap();
+    }
+
+    let mut one_text = FilePc::new();
+    let data = include_bytes!("../../README.md")[..6000].to_vec();
+    one_text.add_file_raw("README.md", data).unwrap();
+
+    let mut image = FilePc::new();
+    let data = include_bytes!("../../test_assets/ricky.webp").to_vec();
+    image.add_file_raw("ricky.webp", data).unwrap();
+
+    vec![
+        ("small text files", small_text),
+        ("one text file", one_text),
+        ("image", image),
+    ]
+}
+
+// Not timed, prints how many data mons each workload takes with and without the archive
+fn print_capacity() {
+    for (name, file_pc) in workloads() {
+        let used = |archive| {
+            let options = PcEncodeOptions {
+                archive,
+                ..Default::default()
+            };
+            match file_pc.as_pc_with_options(&options) {
+                Ok(pc) => pc.usage().unwrap().used_mons.to_string(),
+                Err(_) => "doesn't fit".to_string(),
+            }
+        };
+        println!(
+            "{}: {} mons per file, {} mons archived",
+            name,
+            used(false),
+            used(true)
+        );
+    }
+}
+
 fn bench(c: &mut Criterion) {
     let mut input_data = vec![0; PC::byte_count()];
-    for i in 0..PC::byte_count() {
-        input_data[i] = (i % 255) as u8;
+    for (i, byte) in input_data.iter_mut().enumerate() {
+        *byte = (i % 255) as u8;
     }
 
     let pc = write_and_pc(input_data.clone());
@@ -38,5 +100,28 @@ fn bench(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench);
+fn bench_archive(c: &mut Criterion) {
+    print_capacity();
+
+    let (_, file_pc) = workloads().remove(0);
+    let options = PcEncodeOptions {
+        archive: true,
+        ..Default::default()
+    };
+
+    let mut group = c.benchmark_group("archive");
+    group.sample_size(20);
+
+    group.bench_function("per file", |b| {
+        b.iter(|| black_box(&file_pc).as_pc().unwrap())
+    });
+
+    group.bench_function("archived", |b| {
+        b.iter(|| black_box(&file_pc).as_pc_with_options(&options).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench, bench_archive);
 criterion_main!(benches);
@@ -0,0 +1,44 @@
+// Trains the dictionary used by Codec::ZstdDictionary. The shipped one was trained on the
+// text in the repository's first commit, from the workspace root:
+//   git worktree add ../corpus bfce305
+//   cargo run -p mon-fs-box --example train_zstd_dictionary -- box/assets/zstd_files.dict \
+//       ../corpus/README.md ../corpus/box/src ../corpus/mon-fs/src ../corpus/web-box/src \
+//       ../corpus/guide-site/src
+// which gives the same bytes every time. Once released the dictionary must never be replaced
+use std::path::Path;
+
+const DICTIONARY_SIZE: usize = 8 * 1024;
+// Files stored in a PC are at most a few KB so train on pieces of that size
+const SAMPLE_SIZE: usize = 1024;
+
+fn add_samples(path: &Path, samples: &mut Vec<Vec<u8>>) -> std::io::Result<()> {
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            add_samples(&entry.path(), samples)?;
+        }
+        return Ok(());
+    }
+
+    let data = std::fs::read(path)?;
+    // Only text, binary formats are mostly compressed already
+    if std::str::from_utf8(&data).is_ok() {
+        samples.extend(data.chunks(SAMPLE_SIZE).map(|chunk| chunk.to_vec()));
+    }
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let output = args.next().expect("output path");
+
+    let mut samples = vec![];
+    for folder in args {
+        add_samples(Path::new(&folder), &mut samples)?;
+    }
+
+    let dictionary = zstd::dict::from_samples(&samples, DICTIONARY_SIZE)?;
+    std::fs::write(output, dictionary)?;
+    Ok(())
+}
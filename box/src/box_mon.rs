@@ -1,9 +1,13 @@
+use crate::mon_captured_ball::BoxMonCapturedBall;
 use crate::mon_field::{
-    BitCount, FromGameValueError, FromStringInput, GameSerializer, ToGameValueError,
+    BitCount, FromGameValueError, FromRepresentation, FromStringInput, GameSerializer, Radix,
+    ToGameValueError,
 };
 use crate::mon_gender::BoxMonGender;
 use crate::mon_held_item::BoxMonHeldItem;
-use crate::mon_name::BoxMonName;
+use crate::mon_markings::BoxMonMarkings;
+use crate::mon_name::{BoxMonName, NameAlphabet};
+use crate::mon_nature::BoxMonNature;
 use crate::mon_species::BoxMonSpecies;
 use crate::BoxMonBitVec;
 use bit_vec::BitVec;
@@ -15,6 +19,13 @@ pub struct BoxMon {
     pub gender: BoxMonGender,
     pub name: BoxMonName,
     pub held_item: BoxMonHeldItem,
+    // Only holds data when the layout uses the ball, older guides don't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_ball: Option<BoxMonCapturedBall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markings: Option<BoxMonMarkings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nature: Option<BoxMonNature>,
 }
 
 #[derive(Debug)]
@@ -23,17 +34,221 @@ pub enum StringMonParseError {
     InvalidGender,
     InvalidName(String),
     InvalidItem,
+    InvalidBall,
+    InvalidMarkings,
+    InvalidNature,
 }
 
 impl BitCount for BoxMon {
     fn bit_count() -> usize {
-        BoxMonSpecies::bit_count()
-            + BoxMonGender::bit_count()
-            + BoxMonName::bit_count()
-            + BoxMonHeldItem::bit_count()
+        MonLayout::default().bit_count()
     }
 }
 
+// How data is spread over the fields of the mons
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MonPacking {
+    // Every field holds a whole number of bits
+    #[default]
+    Bits,
+    // Runs of this many mons are one mixed radix number so no field values go unused
+    MixedRadix(usize),
+}
+
+// Which fields of a mon hold data and how they are encoded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonLayout {
+    pub alphabet: NameAlphabet,
+    pub packing: MonPacking,
+    // Mons must be caught in one of the balls in BoxMonCapturedBall
+    pub captured_ball: bool,
+    // Set from the PC menu so they cost nothing to catch
+    pub markings: bool,
+    // Mons must have the nature, easiest with a Synchronize lead
+    pub nature: bool,
+}
+
+impl MonLayout {
+    // Bits each mon holds, with mixed radix packing a run can hold a little more than this
+    pub fn bit_count(&self) -> usize {
+        match self.packing {
+            MonPacking::Bits => self.fields().iter().map(|(_, bits)| bits).sum(),
+            MonPacking::MixedRadix(_) => {
+                (u128::BITS - 1 - self.possible_values().leading_zeros()) as usize
+            }
+        }
+    }
+
+    // Number of different mons that can be written
+    pub fn possible_values(&self) -> u128 {
+        self.fields().iter().map(|(radix, _)| radix).product()
+    }
+
+    // (radix, bits) of every field holding data, least significant first
+    fn fields(&self) -> Vec<(u128, usize)> {
+        let mut fields = vec![
+            (BoxMonSpecies::radix(), BoxMonSpecies::bit_count()),
+            (BoxMonGender::radix(), BoxMonGender::bit_count()),
+            (
+                self.alphabet.name_combinations(),
+                self.alphabet.name_bit_count(),
+            ),
+            (BoxMonHeldItem::radix(), BoxMonHeldItem::bit_count()),
+        ];
+        if self.captured_ball {
+            fields.push((BoxMonCapturedBall::radix(), BoxMonCapturedBall::bit_count()));
+        }
+        if self.markings {
+            fields.push((BoxMonMarkings::radix(), BoxMonMarkings::bit_count()));
+        }
+        if self.nature {
+            fields.push((BoxMonNature::radix(), BoxMonNature::bit_count()));
+        }
+        fields
+    }
+
+    // Clears the fields this layout doesn't hold so they can't be mistaken for data
+    pub fn strip_unused(&self, mon: &mut BoxMon) {
+        if !self.captured_ball {
+            mon.captured_ball = None;
+        }
+        if !self.markings {
+            mon.markings = None;
+        }
+        if !self.nature {
+            mon.nature = None;
+        }
+    }
+
+    fn mon_to_digits(&self, mon: &BoxMon) -> Result<Vec<u128>, FromGameValueError> {
+        let mut digits = vec![
+            mon.species.to_u8() as u128,
+            mon.gender.to_u8() as u128,
+            self.alphabet.name_to_value(&mon.name)?,
+            mon.held_item.to_u8() as u128,
+        ];
+        if self.captured_ball {
+            digits.push(optional_digit(mon.captured_ball)?);
+        }
+        if self.markings {
+            digits.push(optional_digit(mon.markings)?);
+        }
+        if self.nature {
+            digits.push(optional_digit(mon.nature)?);
+        }
+        Ok(digits)
+    }
+
+    fn digits_to_mon(&self, digits: &[u128]) -> Result<BoxMon, ToGameValueError> {
+        let mut digits = digits.iter().copied();
+        let mut next = || digits.next().ok_or(ToGameValueError::BadBitsLength);
+
+        let species = field_from_digit(next()?)?;
+        let gender = field_from_digit(next()?)?;
+        let name = self.alphabet.value_to_name(next()?)?;
+        let held_item = field_from_digit(next()?)?;
+        let captured_ball = match self.captured_ball {
+            true => Some(field_from_digit(next()?)?),
+            false => None,
+        };
+        let markings = match self.markings {
+            true => Some(field_from_digit(next()?)?),
+            false => None,
+        };
+        let nature = match self.nature {
+            true => Some(field_from_digit(next()?)?),
+            false => None,
+        };
+
+        Ok(BoxMon {
+            species,
+            gender,
+            name,
+            held_item,
+            captured_ball,
+            markings,
+            nature,
+        })
+    }
+
+    pub fn mon_to_value(&self, mon: &BoxMon) -> Result<u128, FromGameValueError> {
+        let digits = self.mon_to_digits(mon)?;
+        Ok(digits
+            .iter()
+            .zip(self.fields())
+            .rev()
+            .fold(0, |value, (digit, (radix, _))| value * radix + digit))
+    }
+
+    pub fn value_to_mon(&self, mut value: u128) -> Result<BoxMon, ToGameValueError> {
+        if value >= self.possible_values() {
+            return Err(ToGameValueError::BitsValueOutOfRange);
+        }
+
+        let mut digits = vec![];
+        for (radix, _) in self.fields() {
+            digits.push(value % radix);
+            value /= radix;
+        }
+        self.digits_to_mon(&digits)
+    }
+
+    pub fn bits_to_mon(&self, value: &BoxMonBitVec) -> Result<BoxMon, ToGameValueError> {
+        if value.0.len() != self.bit_count() {
+            return Err(ToGameValueError::BadBitsLength);
+        }
+
+        if let MonPacking::MixedRadix(_) = self.packing {
+            return self.value_to_mon(value.as_u128());
+        }
+
+        let mut digits = vec![];
+        let mut offset = 0;
+        for (_, bits) in self.fields() {
+            digits.push(value.chunk(offset, offset + bits).as_u128());
+            offset += bits;
+        }
+        self.digits_to_mon(&digits)
+    }
+
+    pub fn mon_to_bits(&self, mon: &BoxMon) -> Result<BoxMonBitVec, FromGameValueError> {
+        if let MonPacking::MixedRadix(_) = self.packing {
+            let value = self.mon_to_value(mon)?;
+            if value >> self.bit_count() != 0 {
+                return Err(FromGameValueError::UnsupportedValue);
+            }
+            return Ok(BoxMonBitVec::new_from_raw(&value.to_le_bytes()).chunk(0, self.bit_count()));
+        }
+
+        let mut bits = BitVec::new();
+        for (digit, (_, bit_count)) in self.mon_to_digits(mon)?.iter().zip(self.fields()) {
+            // Values past the last power of two don't fit in the bits
+            if digit >> bit_count != 0 {
+                return Err(FromGameValueError::UnsupportedValue);
+            }
+            let digit_bits = BoxMonBitVec::new_from_raw(&digit.to_le_bytes()).chunk(0, bit_count);
+            bits.extend(digit_bits.0.iter());
+        }
+
+        Ok(BoxMonBitVec(bits))
+    }
+}
+
+// Fields the layout holds must be filled in
+fn optional_digit<T: FromRepresentation>(field: Option<T>) -> Result<u128, FromGameValueError> {
+    match field {
+        Some(field) => Ok(field.to_u8() as u128),
+        None => Err(FromGameValueError::UnsupportedValue),
+    }
+}
+
+fn field_from_digit<T: FromRepresentation>(digit: u128) -> Result<T, ToGameValueError> {
+    u8::try_from(digit)
+        .ok()
+        .and_then(T::from_repr)
+        .ok_or(ToGameValueError::BitsValueOutOfRange)
+}
+
 impl BoxMon {
     pub fn try_from_strings_mon(raw: StringsMon) -> Result<Self, StringMonParseError> {
         let species = match BoxMonSpecies::try_from_string(&raw.species) {
@@ -56,11 +271,38 @@ impl BoxMon {
             None => return Err(StringMonParseError::InvalidItem),
         };
 
+        let captured_ball = match raw.ball.as_str() {
+            "" => None,
+            ball => match BoxMonCapturedBall::try_from_string(ball) {
+                Some(ball) => Some(ball),
+                None => return Err(StringMonParseError::InvalidBall),
+            },
+        };
+
+        let markings = match &raw.markings {
+            None => None,
+            Some(markings) => match BoxMonMarkings::try_from_string(markings) {
+                Some(markings) => Some(markings),
+                None => return Err(StringMonParseError::InvalidMarkings),
+            },
+        };
+
+        let nature = match &raw.nature {
+            None => None,
+            Some(nature) => match BoxMonNature::try_from_string(nature) {
+                Some(nature) => Some(nature),
+                None => return Err(StringMonParseError::InvalidNature),
+            },
+        };
+
         Ok(BoxMon {
             species,
             gender,
             name,
             held_item,
+            captured_ball,
+            markings,
+            nature,
         })
     }
 }
@@ -70,80 +312,14 @@ impl GameSerializer for BoxMon {
     where
         Self: Sized,
     {
-        if value.0.len() != Self::bit_count() {
-            return Err(ToGameValueError::BadBitsLength);
-        }
-
-        let mut offset = 0;
-        let species =
-            match BoxMonSpecies::bits_to_game_value(&value.chunk(0, BoxMonSpecies::bit_count())) {
-                Ok(species) => species,
-                Err(err) => return Err(err),
-            };
-        offset += BoxMonSpecies::bit_count();
-
-        let gender = match BoxMonGender::bits_to_game_value(
-            &value.chunk(offset, offset + BoxMonGender::bit_count()),
-        ) {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        offset += BoxMonGender::bit_count();
-
-        let name = match BoxMonName::bits_to_game_value(
-            &value.chunk(offset, offset + BoxMonName::bit_count()),
-        ) {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        offset += BoxMonName::bit_count();
-
-        let held_item = match BoxMonHeldItem::bits_to_game_value(
-            &value.chunk(offset, offset + BoxMonHeldItem::bit_count()),
-        ) {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-
-        Ok(BoxMon {
-            species,
-            gender,
-            name,
-            held_item,
-        })
+        MonLayout::default().bits_to_mon(value)
     }
 
     fn game_value_to_bits(&self) -> Result<BoxMonBitVec, FromGameValueError>
     where
         Self: Sized,
     {
-        let mut bits = BitVec::new();
-
-        let next_set = match self.species.game_value_to_bits() {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        bits.extend(next_set.0.iter());
-
-        let next_set = match self.gender.game_value_to_bits() {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        bits.extend(next_set.0.iter());
-
-        let next_set = match self.name.game_value_to_bits() {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        bits.extend(next_set.0.iter());
-
-        let next_set = match self.held_item.game_value_to_bits() {
-            Ok(val) => val,
-            Err(err) => return Err(err),
-        };
-        bits.extend(next_set.0.iter());
-
-        Ok(BoxMonBitVec(bits))
+        MonLayout::default().mon_to_bits(self)
     }
 }
 
@@ -153,6 +329,15 @@ pub struct StringsMon {
     pub species: String,
     pub gender: String,
     pub item: String,
+    // Blank when the ball wasn't read
+    #[serde(default)]
+    pub ball: String,
+    // None when the markings weren't read, an empty string is a mon without any
+    #[serde(default)]
+    pub markings: Option<String>,
+    // None when the nature wasn't read
+    #[serde(default)]
+    pub nature: Option<String>,
 }
 
 #[cfg(test)]
@@ -184,6 +369,9 @@ mod tests {
                 BoxMonCharacter::Seven,
             ]),
             held_item: BoxMonHeldItem::Empty,
+            captured_ball: None,
+            markings: None,
+            nature: None,
         };
 
         let bits = mon.game_value_to_bits().unwrap();
@@ -191,6 +379,86 @@ mod tests {
         assert_eq!(mon, recreated_mon);
     }
 
+    #[test]
+    fn captured_ball_layout() {
+        let mut mon = BoxMon::try_from_strings_mon(StringsMon {
+            name: "ADBASDGADS".to_string(),
+            species: "TAILLOW".to_string(),
+            gender: "F".to_string(),
+            item: "REPEL".to_string(),
+            ball: "NET BALL".to_string(),
+            markings: None,
+            nature: None,
+        })
+        .unwrap();
+        assert_eq!(mon.captured_ball, Some(BoxMonCapturedBall::ItemNetBall));
+
+        for packing in [MonPacking::Bits, MonPacking::MixedRadix(1)] {
+            let layout = MonLayout {
+                packing,
+                captured_ball: true,
+                ..Default::default()
+            };
+            let bits = layout.mon_to_bits(&mon).unwrap();
+            assert_eq!(bits.0.len(), BoxMon::bit_count() + 3);
+            assert_eq!(layout.bits_to_mon(&bits).unwrap(), mon);
+        }
+
+        // Layouts without the ball ignore it
+        let bits = mon.game_value_to_bits().unwrap();
+        assert_eq!(
+            BoxMon::bits_to_game_value(&bits).unwrap().captured_ball,
+            None
+        );
+
+        mon.captured_ball = None;
+        let layout = MonLayout {
+            captured_ball: true,
+            ..Default::default()
+        };
+        assert!(layout.mon_to_bits(&mon).is_err());
+    }
+
+    #[test]
+    fn markings_and_nature_layout() {
+        let mut mon = BoxMon::try_from_strings_mon(StringsMon {
+            name: "ADBASDGADS".to_string(),
+            species: "WHISMUR".to_string(),
+            gender: "M".to_string(),
+            item: "".to_string(),
+            ball: "".to_string(),
+            markings: Some("■♥".to_string()),
+            nature: Some("ADAMANT".to_string()),
+        })
+        .unwrap();
+        assert_eq!(mon.markings.unwrap().to_u8(), 0b1010);
+        assert_eq!(mon.nature, Some(BoxMonNature::ADAMANT));
+
+        let layout = MonLayout {
+            markings: true,
+            nature: true,
+            ..Default::default()
+        };
+        let bits = layout.mon_to_bits(&mon).unwrap();
+        assert_eq!(bits.0.len(), BoxMon::bit_count() + 8);
+        assert_eq!(layout.bits_to_mon(&bits).unwrap(), mon);
+
+        // Only 16 of the 25 natures fit in whole bits, mixed radix packing uses them all
+        mon.nature = Some(BoxMonNature::QUIRKY);
+        assert!(layout.mon_to_bits(&mon).is_err());
+        let layout = MonLayout {
+            packing: MonPacking::MixedRadix(1),
+            ..layout
+        };
+        let value = layout.mon_to_value(&mon).unwrap();
+        assert_eq!(layout.value_to_mon(value).unwrap(), mon);
+
+        layout.strip_unused(&mut mon);
+        assert!(mon.markings.is_some());
+        MonLayout::default().strip_unused(&mut mon);
+        assert_eq!((mon.markings, mon.nature), (None, None));
+    }
+
     #[test]
     fn parse_from_raw() {
         let raw = StringsMon {
@@ -198,6 +466,9 @@ mod tests {
             species: "POOCHYENA".to_string(),
             gender: "M".to_string(),
             item: "".to_string(),
+            ball: "".to_string(),
+            markings: None,
+            nature: None,
         };
 
         let mon = BoxMon::try_from_strings_mon(raw).unwrap();
@@ -0,0 +1,307 @@
+use std::io::{Read, Write};
+
+use flate2::{
+    read::{DeflateDecoder, ZlibDecoder},
+    write::{DeflateEncoder, ZlibEncoder},
+    Compression,
+};
+#[cfg(feature = "lzma")]
+use lzma_rust2::{lzma_get_memory_usage, LzmaOptions, LzmaReader, LzmaWriter};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, FromRepr};
+
+use crate::{error::MonFsError, mon_field::ByteCount, pc::PC, pc_set::MAX_SET_SIZE};
+
+// Low bits of PcFile attributes, 0x01 was the only compressed flag before codecs so it has to
+// stay zlib
+pub const CODEC_MASK: u8 = 0x0f;
+
+pub const COMPRESSION_LEVEL: Compression = Compression::best();
+
+// Trained on small text and source files by examples/train_zstd_dictionary.rs, the example
+// lists the corpus. PCs compressed with it need the exact same bytes to decode, so once
+// released it can never change, a new dictionary needs a new codec
+#[cfg(feature = "zstd")]
+const ZSTD_DICTIONARY: &[u8] = include_bytes!("../assets/zstd_files.dict");
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 22;
+#[cfg(feature = "brotli")]
+const BROTLI_QUALITY: u32 = 11;
+#[cfg(feature = "brotli")]
+const BROTLI_WINDOW: u32 = 22;
+#[cfg(feature = "lzma")]
+const LZMA_PRESET: u32 = 9;
+#[cfg(any(feature = "brotli", feature = "lzma"))]
+const BUFFER_SIZE: usize = 4096;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter, FromRepr,
+)]
+#[repr(u8)]
+pub enum Codec {
+    Stored = 0,
+    Zlib = 1,
+    Deflate = 2,
+    ZstdDictionary = 3,
+    Brotli = 4,
+    Lzma = 5,
+}
+
+// Every codec PcFile::new tries, on a tie the first one wins. Codecs left out of the build
+// by their feature can't compress or decompress
+pub const CODECS: &[Codec] = &[
+    Codec::Zlib,
+    Codec::Deflate,
+    #[cfg(feature = "zstd")]
+    Codec::ZstdDictionary,
+    #[cfg(feature = "brotli")]
+    Codec::Brotli,
+    #[cfg(feature = "lzma")]
+    Codec::Lzma,
+];
+
+// Nothing bigger than a full set of PCs is worth compressing, so no LZMA window has to be
+// bigger either. Decoding stops reading past it so a small PC can't inflate without bound
+fn size_limit() -> usize {
+    PC::byte_count() * MAX_SET_SIZE
+}
+
+impl Codec {
+    pub fn from_attributes(attributes: u8) -> Result<Codec, MonFsError> {
+        let id = attributes & CODEC_MASK;
+        Codec::from_repr(id).ok_or(MonFsError::UnknownCodec(id))
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, MonFsError> {
+        if data.len() > size_limit() {
+            return Err(self.too_big());
+        }
+        self.try_compress(data).map_err(|err| self.error(err))
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, MonFsError> {
+        let decompressed = self.try_decompress(data).map_err(|err| self.error(err))?;
+        if decompressed.len() > size_limit() {
+            return Err(self.too_big());
+        }
+        Ok(decompressed)
+    }
+
+    fn try_compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Codec::Stored => Ok(data.to_vec()),
+            Codec::Zlib => {
+                let mut e = ZlibEncoder::new(Vec::new(), COMPRESSION_LEVEL);
+                e.write_all(data)?;
+                e.finish()
+            }
+            Codec::Deflate => {
+                let mut e = DeflateEncoder::new(Vec::new(), COMPRESSION_LEVEL);
+                e.write_all(data)?;
+                e.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Codec::ZstdDictionary => {
+                let mut e =
+                    zstd::Encoder::with_dictionary(Vec::new(), ZSTD_LEVEL, ZSTD_DICTIONARY)?;
+                // Both are already known when decoding
+                e.include_checksum(false)?;
+                e.include_dictid(false)?;
+                e.write_all(data)?;
+                e.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Codec::Brotli => {
+                let mut e = brotli::CompressorWriter::new(
+                    Vec::new(),
+                    BUFFER_SIZE,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW,
+                );
+                e.write_all(data)?;
+                Ok(e.into_inner())
+            }
+            #[cfg(feature = "lzma")]
+            Codec::Lzma => {
+                // The default window is far bigger than anything that fits in a PC and the
+                // decoder has to allocate all of it
+                let mut options = LzmaOptions::with_preset(LZMA_PRESET);
+                options.dict_size =
+                    (data.len() as u32).clamp(BUFFER_SIZE as u32, size_limit() as u32);
+                let mut e =
+                    LzmaWriter::new_use_header(Vec::new(), &options, Some(data.len() as u64))?;
+                e.write_all(data)?;
+                e.finish()
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(not_built()),
+        }
+    }
+
+    // Reads one byte past the limit so decompress can tell the data was cut off
+    fn try_decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let limit = size_limit() as u64 + 1;
+        let mut decompressed = Vec::new();
+        match self {
+            Codec::Stored => decompressed.extend_from_slice(data),
+            Codec::Zlib => {
+                ZlibDecoder::new(data)
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            Codec::Deflate => {
+                DeflateDecoder::new(data)
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            #[cfg(feature = "zstd")]
+            Codec::ZstdDictionary => {
+                zstd::Decoder::with_dictionary(data, ZSTD_DICTIONARY)?
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            #[cfg(feature = "brotli")]
+            Codec::Brotli => {
+                brotli::Decompressor::new(data, BUFFER_SIZE)
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            #[cfg(feature = "lzma")]
+            Codec::Lzma => {
+                let options = LzmaOptions::with_preset(LZMA_PRESET);
+                let mem_limit_kb =
+                    lzma_get_memory_usage(size_limit() as u32, options.lc, options.lp)?;
+                LzmaReader::new_mem_limit(data, mem_limit_kb, None)?
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(not_built()),
+        }
+        Ok(decompressed)
+    }
+
+    fn too_big(&self) -> MonFsError {
+        MonFsError::Codec {
+            codec: *self,
+            error: std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "data is bigger than a full set of PCs",
+            ),
+        }
+    }
+
+    fn error(&self, err: std::io::Error) -> MonFsError {
+        match self {
+            Codec::Zlib => MonFsError::Zlib(err),
+            _ => MonFsError::Codec {
+                codec: *self,
+                error: err,
+            },
+        }
+    }
+}
+
+fn not_built() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "codec left out of this build",
+    )
+}
+
+// The codec that makes the data smallest, stored when none of them help
+pub fn compress_smallest(data: &[u8]) -> Result<(Codec, Vec<u8>), MonFsError> {
+    let mut best = (Codec::Stored, data.to_vec());
+    for &codec in CODECS {
+        let compressed = codec.compress(data)?;
+        if compressed.len() < best.1.len() {
+            best = (codec, compressed);
+        }
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_codec_round_trips() {
+        let text = include_bytes!("../../README.md");
+        let image = include_bytes!("../../test_assets/ricky.webp");
+        for &codec in [Codec::Stored].iter().chain(CODECS) {
+            for data in [&text[..], &image[..], &[], &[7]] {
+                let compressed = codec.compress(data).unwrap();
+                assert_eq!(codec.decompress(&compressed).unwrap(), data, "{codec}");
+            }
+        }
+    }
+
+    #[test]
+    fn smallest_codec_is_kept() {
+        let text =
+            b"mon-fs stores files as pokemon in the PC, every byte costs a caught mon. ".repeat(4);
+        let (codec, compressed) = compress_smallest(&text).unwrap();
+        for other in CODECS {
+            assert!(compressed.len() <= other.compress(&text).unwrap().len());
+        }
+        assert_eq!(codec.decompress(&compressed).unwrap(), text);
+
+        let (codec, _) = compress_smallest(&[1, 2, 3]).unwrap();
+        assert_eq!(codec, Codec::Stored);
+        assert!(matches!(
+            Codec::from_attributes(0x0e),
+            Err(MonFsError::UnknownCodec(0x0e))
+        ));
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn lzma_windows_bigger_than_a_set_are_refused() {
+        let data = vec![7; 100];
+        let mut compressed = Codec::Lzma.compress(&data).unwrap();
+        assert_eq!(Codec::Lzma.decompress(&compressed).unwrap(), data);
+
+        // The window size follows the properties byte
+        compressed[1..5].copy_from_slice(&(size_limit() as u32 * 2).to_le_bytes());
+        assert!(matches!(
+            Codec::Lzma.decompress(&compressed),
+            Err(MonFsError::Codec {
+                codec: Codec::Lzma,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn data_bigger_than_a_set_is_refused() {
+        // Long runs of one byte overflow the test thread's stack in zlib-rs debug builds
+        let data: Vec<u8> = (0..=size_limit()).map(|i| (i % 251) as u8).collect();
+        for &codec in [Codec::Stored].iter().chain(CODECS) {
+            assert!(
+                matches!(
+                    codec.compress(&data),
+                    Err(MonFsError::Codec { codec: c, .. }) if c == codec
+                ),
+                "{codec}"
+            );
+
+            // Written without the check, like a PC made to inflate
+            let compressed = codec.try_compress(&data).unwrap();
+            assert!(
+                matches!(
+                    codec.decompress(&compressed),
+                    Err(MonFsError::Codec { codec: c, .. }) if c == codec
+                ),
+                "{codec}"
+            );
+
+            let compressed = codec.compress(&data[1..]).unwrap();
+            assert_eq!(
+                codec.decompress(&compressed).unwrap().len(),
+                size_limit(),
+                "{codec}"
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{codec::CODEC_MASK, error::MonFsError, file_pc::PcFile};
+
+// How FilePc is laid out from format version 1, replacing bincode. A container is a version
+// byte then its file records one after another until the data runs out, so appending a file
+// only appends bytes. Each record is
+//   flags       varint, codec in the low 4 bits, FLAG_MODE, FLAG_MODIFIED and FLAG_HASH
+//   name        varint length then UTF-8
+//   mode        varint, only with FLAG_MODE
+//   modified    zigzag varint, only with FLAG_MODIFIED
+//   hash        CRC32 of the uncompressed data, 4 bytes little endian, only with FLAG_HASH
+//   data        varint length then the bytes
+// Varints are LEB128, 7 bits a byte least significant first, and must use as few bytes as
+// possible
+pub const CONTAINER_VERSION: u8 = 1;
+
+const FLAG_MODE: u64 = 0x10;
+const FLAG_MODIFIED: u64 = 0x20;
+const FLAG_HASH: u64 = 0x40;
+const KNOWN_FLAGS: u64 = CODEC_MASK as u64 | FLAG_MODE | FLAG_MODIFIED | FLAG_HASH;
+
+// Offsets are from the start of the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerError {
+    Truncated { offset: usize },
+    VarintOverflow { offset: usize },
+    OverlongVarint { offset: usize },
+    UnknownFlags { offset: usize, flags: u64 },
+    BadName { offset: usize },
+    OutOfRange { offset: usize },
+    // Written by a newer mon-fs
+    UnknownVersion(u8),
+}
+
+pub fn write_file(out: &mut Vec<u8>, file: &PcFile) {
+    let mut flags = file.attributes as u64;
+    if file.mode.is_some() {
+        flags |= FLAG_MODE;
+    }
+    if file.modified.is_some() {
+        flags |= FLAG_MODIFIED;
+    }
+    if file.hash.is_some() {
+        flags |= FLAG_HASH;
+    }
+
+    write_varint(out, flags);
+    write_varint(out, file.name.len() as u64);
+    out.extend_from_slice(file.name.as_bytes());
+    if let Some(mode) = file.mode {
+        write_varint(out, mode as u64);
+    }
+    if let Some(modified) = file.modified {
+        write_varint(out, ((modified << 1) ^ (modified >> 63)) as u64);
+    }
+    if let Some(hash) = file.hash {
+        out.extend_from_slice(&hash.to_le_bytes());
+    }
+    write_varint(out, file.data.len() as u64);
+    out.extend_from_slice(&file.data);
+}
+
+pub fn write_files(files: &[PcFile]) -> Vec<u8> {
+    let mut out = vec![CONTAINER_VERSION];
+    for file in files {
+        write_file(&mut out, file);
+    }
+    out
+}
+
+pub fn read_files(data: &[u8]) -> Result<Vec<PcFile>, MonFsError> {
+    let mut reader = Reader { data, offset: 0 };
+    let version = reader.take(1)?[0];
+    if version != CONTAINER_VERSION {
+        return Err(ContainerError::UnknownVersion(version).into());
+    }
+
+    let mut files = vec![];
+    while reader.offset < data.len() {
+        files.push(reader.file()?);
+    }
+    Ok(files)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn file(&mut self) -> Result<PcFile, ContainerError> {
+        let start = self.offset;
+        let flags = self.varint()?;
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(ContainerError::UnknownFlags {
+                offset: start,
+                flags,
+            });
+        }
+
+        let name_start = self.offset;
+        let name = self.bytes()?;
+        let name = match String::from_utf8(name.to_vec()) {
+            Ok(name) => name,
+            Err(_) => return Err(ContainerError::BadName { offset: name_start }),
+        };
+
+        let mode = match flags & FLAG_MODE {
+            0 => None,
+            _ => Some(self.u32()?),
+        };
+        let modified = match flags & FLAG_MODIFIED {
+            0 => None,
+            _ => {
+                let zigzag = self.varint()?;
+                Some((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+            }
+        };
+        let hash = match flags & FLAG_HASH {
+            0 => None,
+            _ => Some(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+        };
+        let data = self.bytes()?.to_vec();
+
+        Ok(PcFile {
+            name,
+            attributes: (flags & CODEC_MASK as u64) as u8,
+            data,
+            mode,
+            modified,
+            hash,
+        })
+    }
+
+    fn varint(&mut self) -> Result<u64, ContainerError> {
+        let start = self.offset;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .data
+                .get(self.offset)
+                .ok_or(ContainerError::Truncated {
+                    offset: self.offset,
+                })?;
+            self.offset += 1;
+
+            let bits = (byte & 0x7f) as u64;
+            if shift == 63 && bits > 1 {
+                return Err(ContainerError::VarintOverflow { offset: start });
+            }
+            value |= bits << shift;
+
+            if byte & 0x80 == 0 {
+                // A trailing zero byte means the same value fits in fewer bytes
+                if byte == 0 && shift > 0 {
+                    return Err(ContainerError::OverlongVarint { offset: start });
+                }
+                return Ok(value);
+            }
+        }
+        Err(ContainerError::VarintOverflow { offset: start })
+    }
+
+    fn u32(&mut self) -> Result<u32, ContainerError> {
+        let start = self.offset;
+        u32::try_from(self.varint()?).map_err(|_| ContainerError::OutOfRange { offset: start })
+    }
+
+    fn bytes(&mut self) -> Result<&[u8], ContainerError> {
+        let start = self.offset;
+        let len = usize::try_from(self.varint()?)
+            .map_err(|_| ContainerError::OutOfRange { offset: start })?;
+        self.take(len)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], ContainerError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(ContainerError::Truncated {
+                offset: self.data.len(),
+            })?;
+
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(
+        name: &str,
+        data: Vec<u8>,
+        mode: Option<u32>,
+        modified: Option<i64>,
+        hash: Option<u32>,
+    ) -> PcFile {
+        PcFile {
+            name: name.to_string(),
+            attributes: 1,
+            data,
+            mode,
+            modified,
+            hash,
+        }
+    }
+
+    #[test]
+    fn files_round_trip_with_little_overhead() {
+        let files = vec![
+            file("a.txt", vec![1, 2, 3], None, None, None),
+            file(
+                "docs/b.md",
+                vec![7; 300],
+                Some(0o100644),
+                Some(1_700_000_000),
+                None,
+            ),
+            file("old", vec![], Some(u32::MAX), Some(i64::MIN), Some(0)),
+            file("", vec![0], None, Some(-1), Some(u32::MAX)),
+            file("hashed", vec![5; 5], None, None, Some(0xdead_beef)),
+        ];
+
+        let data = write_files(&files);
+        let decoded = read_files(&data).unwrap();
+        assert_eq!(decoded.len(), files.len());
+        for (decoded, file) in decoded.iter().zip(&files) {
+            assert_eq!(decoded.name, file.name);
+            assert_eq!(decoded.attributes, file.attributes);
+            assert_eq!(decoded.data, file.data);
+            assert_eq!(decoded.mode, file.mode);
+            assert_eq!(decoded.modified, file.modified);
+            assert_eq!(decoded.hash, file.hash);
+        }
+
+        // Version, flags and two lengths instead of bincode's 17 bytes
+        assert_eq!(write_files(&files[..1]).len(), 1 + 3 + 5 + 3);
+        assert!(read_files(&[CONTAINER_VERSION]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_containers_are_rejected() {
+        let data = write_files(&[file("a.txt", vec![1, 2, 3], Some(0o644), None, None)]);
+        for len in 0..data.len() {
+            if len == 1 {
+                continue;
+            }
+            assert!(matches!(
+                read_files(&data[..len]),
+                Err(MonFsError::Container(ContainerError::Truncated { .. }))
+            ));
+        }
+
+        let cases: [(&[u8], ContainerError); 6] = [
+            (&[2], ContainerError::UnknownVersion(2)),
+            (
+                &[1, 0x80, 0x01, 0, 0],
+                ContainerError::UnknownFlags {
+                    offset: 1,
+                    flags: 0x80,
+                },
+            ),
+            (
+                &[1, 0x81, 0x00, 0, 0],
+                ContainerError::OverlongVarint { offset: 1 },
+            ),
+            (&[1, 1, 1, 0xff, 0], ContainerError::BadName { offset: 2 }),
+            (
+                &[1, 0x10, 0, 0x80, 0x80, 0x80, 0x80, 0x10, 0],
+                ContainerError::OutOfRange { offset: 3 },
+            ),
+            (
+                &[
+                    1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02,
+                ],
+                ContainerError::VarintOverflow { offset: 1 },
+            ),
+        ];
+        for (data, error) in cases {
+            match read_files(data) {
+                Err(MonFsError::Container(found)) => assert_eq!(found, error),
+                other => panic!("{:?} gave {:?}", data, other),
+            }
+        }
+    }
+}
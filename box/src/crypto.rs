@@ -0,0 +1,94 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::error::MonFsError;
+
+// Encrypted data is the salt, then the nonce, then the ciphertext with its tag
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 24;
+const TAG_SIZE: usize = 16;
+pub const ENCRYPTION_OVERHEAD: usize = SALT_SIZE + NONCE_SIZE + TAG_SIZE;
+
+// Argon2id with the OWASP recommended cost, spelled out so a new version of the crate
+// changing its defaults can't lock anyone out of their PC
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_LANES: u32 = 1;
+
+pub fn encrypt(data: &[u8], password: &str) -> Result<Vec<u8>, MonFsError> {
+    let mut salt = [0; SALT_SIZE];
+    let mut nonce = [0; NONCE_SIZE];
+    random(&mut salt)?;
+    random(&mut nonce)?;
+
+    let cipher = cipher(password, &salt)?;
+    let ciphertext = match cipher.encrypt(XNonce::from_slice(&nonce), data) {
+        Ok(ciphertext) => ciphertext,
+        Err(_) => return Err(MonFsError::Encryption("encrypting failed".to_string())),
+    };
+
+    let mut encrypted = Vec::with_capacity(ENCRYPTION_OVERHEAD + data.len());
+    encrypted.extend_from_slice(&salt);
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend(ciphertext);
+    Ok(encrypted)
+}
+
+// A wrong password and data that was changed look the same, the tag doesn't match
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, MonFsError> {
+    if data.len() < ENCRYPTION_OVERHEAD {
+        return Err(MonFsError::WrongPassword);
+    }
+    let (salt, rest) = data.split_at(SALT_SIZE);
+    let (nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+
+    cipher(password, salt)?
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| MonFsError::WrongPassword)
+}
+
+fn cipher(password: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, MonFsError> {
+    let params = Params::new(ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_LANES, Some(32))
+        .map_err(|err| MonFsError::Encryption(err.to_string()))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0; 32];
+    argon2
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| MonFsError::Encryption(err.to_string()))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn random(buf: &mut [u8]) -> Result<(), MonFsError> {
+    getrandom::getrandom(buf).map_err(|err| MonFsError::Encryption(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_the_right_password_decrypts() {
+        let data = b"the streamer's secret files".to_vec();
+        let encrypted = encrypt(&data, "hunter2").unwrap();
+        assert_eq!(encrypted.len(), data.len() + ENCRYPTION_OVERHEAD);
+        assert_eq!(decrypt(&encrypted, "hunter2").unwrap(), data);
+
+        assert!(matches!(
+            decrypt(&encrypted, "hunter3"),
+            Err(MonFsError::WrongPassword)
+        ));
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            decrypt(&tampered, "hunter2"),
+            Err(MonFsError::WrongPassword)
+        ));
+
+        // Fresh salt and nonce every time
+        assert_ne!(encrypt(&data, "hunter2").unwrap(), encrypted);
+    }
+}
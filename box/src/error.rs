@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+
+use crate::{
+    codec::Codec,
+    container::ContainerError,
+    fec::FecError,
+    mon_field::{FromGameValueError, ToGameValueError},
+    pc::MonPosition,
+    pc_set::PcSetError,
+    save::SaveError,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum GameValueError {
+    ToGameValue(ToGameValueError),
+    FromGameValue(FromGameValueError),
+}
+
+#[derive(Debug)]
+pub enum MonFsError {
+    Bincode(bincode::Error),
+    Container(ContainerError),
+    Zlib(std::io::Error),
+    Codec {
+        codec: Codec,
+        error: std::io::Error,
+    },
+    UnknownCodec(u8),
+    PasswordNeeded,
+    WrongPassword,
+    Encryption(String),
+    CapacityExceeded {
+        have: usize,
+        need: usize,
+    },
+    InvalidPaddingMon,
+    UnknownAlphabet(u8),
+    // Mons in a mixed radix run, must be between 1 and the number of data mons
+    BadRunLength(usize),
+    // A slot that should hold data is empty
+    MissingMon(MonPosition),
+    OutOfRange {
+        end: usize,
+        len: usize,
+    },
+    UnsupportedVersion(u8),
+    UnknownProfile(u8),
+    BadGameValue {
+        position: MonPosition,
+        error: GameValueError,
+    },
+    BadBox {
+        box_index: usize,
+        error: GameValueError,
+    },
+    MissingBoxes,
+    BadPermutation,
+    // Mons in mixed radix runs can't be moved to reuse owned ones
+    MixedRadixReuse(usize),
+    BadStorage(GameValueError),
+    Fec(FecError),
+    PcSet(PcSetError),
+    Save(SaveError),
+    FileExists(String),
+    FileNotFound(String),
+    HashMismatch(String),
+    BadPath(String),
+    NotAFolder(PathBuf),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for MonFsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonFsError::Bincode(err) => write!(f, "bad PC data: {}", err),
+            MonFsError::Container(err) => write!(f, "bad file container: {:?}", err),
+            MonFsError::Zlib(err) => write!(f, "compression failed: {}", err),
+            MonFsError::Codec { codec, error } => write!(f, "{} failed: {}", codec, error),
+            MonFsError::PasswordNeeded => write!(f, "PC is encrypted, a password is needed"),
+            MonFsError::WrongPassword => {
+                write!(f, "wrong password or the PC was misread")
+            }
+            MonFsError::Encryption(err) => write!(f, "encryption failed: {}", err),
+            MonFsError::UnknownCodec(id) => write!(f, "unknown compression codec {}", id),
+            MonFsError::CapacityExceeded { have, need } => {
+                write!(f, "not enough space in PC have {} need {}", have, need)
+            }
+            MonFsError::InvalidPaddingMon => write!(f, "invalid padding mon"),
+            MonFsError::UnknownAlphabet(id) => write!(f, "unknown name alphabet {}", id),
+            MonFsError::BadRunLength(run_length) => {
+                write!(f, "bad mixed radix run of {} mons", run_length)
+            }
+            MonFsError::MissingMon(position) => write!(
+                f,
+                "box {} slot {} should hold data but is empty",
+                position.box_index + 1,
+                position.slot + 1
+            ),
+            MonFsError::OutOfRange { end, len } => {
+                write!(f, "write ends at byte {} past the {} bytes in PC", end, len)
+            }
+            MonFsError::UnsupportedVersion(version) => write!(
+                f,
+                "PC was written by a newer mon-fs (format version {})",
+                version
+            ),
+            MonFsError::UnknownProfile(id) => write!(f, "unknown encoding profile {}", id),
+            MonFsError::BadGameValue { position, error } => write!(
+                f,
+                "bad mon in box {} slot {}: {:?}",
+                position.box_index + 1,
+                position.slot + 1,
+                error
+            ),
+            MonFsError::BadBox { box_index, error } => write!(
+                f,
+                "bad title or wallpaper for box {}: {:?}",
+                box_index + 1,
+                error
+            ),
+            MonFsError::MissingBoxes => write!(f, "PC is missing its box titles"),
+            MonFsError::BadPermutation => write!(f, "bad slot permutation table"),
+            MonFsError::MixedRadixReuse(run_length) => write!(
+                f,
+                "mixed radix runs of {} mons can't be moved to reuse owned mons",
+                run_length
+            ),
+            MonFsError::BadStorage(error) => write!(f, "bad party or item PC: {:?}", error),
+            MonFsError::Fec(err) => write!(f, "error correction failed: {:?}", err),
+            MonFsError::PcSet(PcSetError::PartOfSet { sequence, total }) => write!(
+                f,
+                "PC is part {} of a set of {}, read it with the rest of the set",
+                sequence, total
+            ),
+            MonFsError::PcSet(PcSetError::Missing(sequence)) => {
+                write!(f, "PC {} of the set is missing", sequence)
+            }
+            MonFsError::PcSet(err) => write!(f, "bad PC set: {:?}", err),
+            MonFsError::Save(err) => write!(f, "bad save: {:?}", err),
+            MonFsError::FileExists(name) => write!(f, "file with name {} already exists", name),
+            MonFsError::FileNotFound(name) => write!(f, "no file with name {}", name),
+            MonFsError::HashMismatch(name) => {
+                write!(f, "{} doesn't match its hash, it was decoded wrong", name)
+            }
+            MonFsError::BadPath(name) => write!(f, "{} is not a plain relative path", name),
+            MonFsError::NotAFolder(path) => write!(f, "{} must be a folder", path.display()),
+            MonFsError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for MonFsError {}
+
+impl From<bincode::Error> for MonFsError {
+    fn from(value: bincode::Error) -> Self {
+        MonFsError::Bincode(value)
+    }
+}
+
+impl From<ContainerError> for MonFsError {
+    fn from(value: ContainerError) -> Self {
+        MonFsError::Container(value)
+    }
+}
+
+impl From<FecError> for MonFsError {
+    fn from(value: FecError) -> Self {
+        MonFsError::Fec(value)
+    }
+}
+
+impl From<PcSetError> for MonFsError {
+    fn from(value: PcSetError) -> Self {
+        MonFsError::PcSet(value)
+    }
+}
+
+impl From<SaveError> for MonFsError {
+    fn from(value: SaveError) -> Self {
+        MonFsError::Save(value)
+    }
+}
+
+impl From<std::io::Error> for MonFsError {
+    fn from(value: std::io::Error) -> Self {
+        // Errors raised while reading a PC are wrapped so they can be recovered here
+        match value.downcast::<MonFsError>() {
+            Ok(err) => err,
+            Err(err) => MonFsError::Io(err),
+        }
+    }
+}
+
+impl From<MonFsError> for std::io::Error {
+    fn from(value: MonFsError) -> Self {
+        match value {
+            MonFsError::Io(err) => err,
+            err => std::io::Error::other(err),
+        }
+    }
+}
@@ -0,0 +1,432 @@
+// Reed-Solomon forward error correction over GF(2^8) for data stored in a PC.
+//
+// The data is left as is and parity is appended so a PC with no errors reads the
+// same as one without FEC. Byte i of the data belongs to codeword i % codewords,
+// so the bytes of a single misread mon are spread over many codewords.
+//
+// Layout: [header x3][data][parity]
+// header: data length (u32 LE), parity symbols per codeword (u8), parity budget (u16 LE)
+// The budget is kept so the data can be encoded again with the same parity
+
+const GF_PRIMITIVE: u16 = 0x11d;
+const MAX_CODEWORD_SIZE: usize = 255;
+const HEADER_SIZE: usize = 7;
+const HEADER_COPIES: usize = 3;
+pub const FEC_HEADER_SIZE: usize = HEADER_SIZE * HEADER_COPIES;
+
+const GF_EXP: [u8; 512] = gf_exp_table();
+const GF_LOG: [u8; 256] = gf_log_table();
+
+const fn gf_exp_table() -> [u8; 512] {
+    let mut table = [0u8; 512];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        table[i] = x as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= GF_PRIMITIVE;
+        }
+        i += 1;
+    }
+    while i < 512 {
+        table[i] = table[i - 255];
+        i += 1;
+    }
+    table
+}
+
+const fn gf_log_table() -> [u8; 256] {
+    let exp = gf_exp_table();
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 255 {
+        table[exp[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FecError {
+    BadHeader,
+    TooShort,
+    Uncorrectable(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Geometry {
+    data_len: usize,
+    parity_symbols: usize,
+    codewords: usize,
+}
+
+impl Geometry {
+    fn new(data_len: usize, parity_symbols: usize) -> Geometry {
+        let data_per_codeword = MAX_CODEWORD_SIZE - parity_symbols;
+        Geometry {
+            data_len,
+            parity_symbols,
+            codewords: data_len.div_ceil(data_per_codeword).max(1),
+        }
+    }
+
+    // Largest parity symbols per codeword that fit in the parity budget
+    fn for_budget(data_len: usize, parity_budget: usize) -> Option<Geometry> {
+        (2..=parity_budget.min(MAX_CODEWORD_SIZE - 1))
+            .rev()
+            .map(|parity_symbols| Geometry::new(data_len, parity_symbols))
+            .find(|geometry| geometry.parity_len() <= parity_budget)
+    }
+
+    fn parity_len(&self) -> usize {
+        self.parity_symbols * self.codewords
+    }
+
+    fn codeword_data_len(&self, codeword: usize) -> usize {
+        (self.data_len + self.codewords - 1 - codeword) / self.codewords
+    }
+
+    // Stream offsets of every symbol in a codeword, data first then parity
+    fn codeword_offsets(&self, codeword: usize) -> Vec<usize> {
+        let data = (0..self.codeword_data_len(codeword)).map(|i| i * self.codewords + codeword);
+        let parity =
+            (0..self.parity_symbols).map(|i| self.data_len + i * self.codewords + codeword);
+        data.chain(parity).collect()
+    }
+}
+
+// Output size of encoding data_len bytes with the given parity budget
+pub fn encoded_len(data_len: usize, parity_budget: usize) -> usize {
+    let parity = Geometry::for_budget(data_len, parity_budget).map_or(0, |g| g.parity_len());
+    FEC_HEADER_SIZE + data_len + parity
+}
+
+pub fn encode(data: &[u8], parity_budget: usize) -> Vec<u8> {
+    let geometry =
+        Geometry::for_budget(data.len(), parity_budget).unwrap_or(Geometry::new(data.len(), 0));
+
+    let mut header = [0; HEADER_SIZE];
+    header[0..4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    header[4] = geometry.parity_symbols as u8;
+    header[5..7].copy_from_slice(&(parity_budget.min(u16::MAX as usize) as u16).to_le_bytes());
+
+    let mut out = Vec::with_capacity(FEC_HEADER_SIZE + data.len() + geometry.parity_len());
+    for _ in 0..HEADER_COPIES {
+        out.extend_from_slice(&header);
+    }
+    out.extend_from_slice(data);
+    out.resize(FEC_HEADER_SIZE + data.len() + geometry.parity_len(), 0);
+
+    let body = &mut out[FEC_HEADER_SIZE..];
+    for codeword in 0..geometry.codewords {
+        let offsets = geometry.codeword_offsets(codeword);
+        let message: Vec<u8> = offsets[..geometry.codeword_data_len(codeword)]
+            .iter()
+            .map(|offset| body[*offset])
+            .collect();
+
+        let parity = rs_encode(&message, geometry.parity_symbols);
+        for (offset, value) in offsets[message.len()..].iter().zip(parity) {
+            body[*offset] = value;
+        }
+    }
+
+    out
+}
+
+// Stream offset and the bits flipped to repair it
+pub type Correction = (usize, u8);
+
+fn read_header(stream: &[u8]) -> Result<[u8; HEADER_SIZE], FecError> {
+    if stream.len() < FEC_HEADER_SIZE {
+        return Err(FecError::TooShort);
+    }
+
+    // Majority vote each header byte across the copies
+    let mut header = [0; HEADER_SIZE];
+    for (i, byte) in header.iter_mut().enumerate() {
+        let copies: Vec<u8> = (0..HEADER_COPIES)
+            .map(|copy| stream[copy * HEADER_SIZE + i])
+            .collect();
+        *byte = if copies[1] == copies[2] {
+            copies[1]
+        } else {
+            copies[0]
+        };
+    }
+    Ok(header)
+}
+
+// The parity budget the stream was encoded with
+pub fn parity_budget(stream: &[u8]) -> Result<usize, FecError> {
+    let header = read_header(stream)?;
+    Ok(u16::from_le_bytes([header[5], header[6]]) as usize)
+}
+
+pub fn decode(stream: &[u8]) -> Result<(Vec<u8>, Vec<Correction>), FecError> {
+    let header = read_header(stream)?;
+    let data_len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let parity_symbols = header[4] as usize;
+    if parity_symbols >= MAX_CODEWORD_SIZE {
+        return Err(FecError::BadHeader);
+    }
+
+    let geometry = Geometry::new(data_len, parity_symbols);
+    if stream.len() < FEC_HEADER_SIZE + data_len + geometry.parity_len() {
+        return Err(FecError::TooShort);
+    }
+
+    let mut body =
+        stream[FEC_HEADER_SIZE..FEC_HEADER_SIZE + data_len + geometry.parity_len()].to_vec();
+    let mut corrected = vec![];
+    if parity_symbols > 0 {
+        for codeword in 0..geometry.codewords {
+            let offsets = geometry.codeword_offsets(codeword);
+            let received: Vec<u8> = offsets.iter().map(|offset| body[*offset]).collect();
+
+            let fixed = match rs_correct(&received, parity_symbols) {
+                Some(fixed) => fixed,
+                None => return Err(FecError::Uncorrectable(codeword)),
+            };
+
+            for (i, offset) in offsets.iter().enumerate() {
+                if fixed[i] != received[i] {
+                    body[*offset] = fixed[i];
+                    corrected.push((FEC_HEADER_SIZE + offset, fixed[i] ^ received[i]));
+                }
+            }
+        }
+    }
+
+    corrected.sort();
+    body.truncate(data_len);
+
+    Ok((body, corrected))
+}
+
+fn gf_mul(x: u8, y: u8) -> u8 {
+    if x == 0 || y == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[x as usize] as usize + GF_LOG[y as usize] as usize]
+}
+
+fn gf_div(x: u8, y: u8) -> u8 {
+    if x == 0 {
+        return 0;
+    }
+    GF_EXP[(GF_LOG[x as usize] as usize + 255 - GF_LOG[y as usize] as usize) % 255]
+}
+
+fn gf_pow2(power: usize) -> u8 {
+    GF_EXP[power % 255]
+}
+
+fn gf_inverse(x: u8) -> u8 {
+    GF_EXP[255 - GF_LOG[x as usize] as usize]
+}
+
+// Polynomials are stored highest degree first
+fn poly_scale(poly: &[u8], x: u8) -> Vec<u8> {
+    poly.iter().map(|p| gf_mul(*p, x)).collect()
+}
+
+fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let len = p.len().max(q.len());
+    let mut result = vec![0; len];
+    for (i, value) in p.iter().enumerate() {
+        result[i + len - p.len()] = *value;
+    }
+    for (i, value) in q.iter().enumerate() {
+        result[i + len - q.len()] ^= *value;
+    }
+    result
+}
+
+fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; p.len() + q.len() - 1];
+    for (j, q_value) in q.iter().enumerate() {
+        for (i, p_value) in p.iter().enumerate() {
+            result[i + j] ^= gf_mul(*p_value, *q_value);
+        }
+    }
+    result
+}
+
+fn poly_eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().fold(0, |y, coef| gf_mul(y, x) ^ coef)
+}
+
+fn generator_poly(parity_symbols: usize) -> Vec<u8> {
+    (0..parity_symbols).fold(vec![1], |g, i| poly_mul(&g, &[1, gf_pow2(i)]))
+}
+
+fn rs_encode(message: &[u8], parity_symbols: usize) -> Vec<u8> {
+    let generator = generator_poly(parity_symbols);
+    let mut remainder = message.to_vec();
+    remainder.resize(message.len() + parity_symbols, 0);
+    for i in 0..message.len() {
+        let coef = remainder[i];
+        if coef != 0 {
+            for (j, g) in generator.iter().enumerate().skip(1) {
+                remainder[i + j] ^= gf_mul(*g, coef);
+            }
+        }
+    }
+    remainder.split_off(message.len())
+}
+
+fn rs_correct(codeword: &[u8], parity_symbols: usize) -> Option<Vec<u8>> {
+    let syndromes: Vec<u8> = (0..parity_symbols)
+        .map(|i| poly_eval(codeword, gf_pow2(i)))
+        .collect();
+    if syndromes.iter().all(|s| *s == 0) {
+        return Some(codeword.to_vec());
+    }
+
+    // Berlekamp-Massey
+    let mut error_locator = vec![1];
+    let mut old_locator = vec![1];
+    for i in 0..parity_symbols {
+        let mut delta = syndromes[i];
+        for j in 1..error_locator.len().min(i + 1) {
+            delta ^= gf_mul(error_locator[error_locator.len() - 1 - j], syndromes[i - j]);
+        }
+        old_locator.push(0);
+        if delta != 0 {
+            if old_locator.len() > error_locator.len() {
+                let new_locator = poly_scale(&old_locator, delta);
+                old_locator = poly_scale(&error_locator, gf_inverse(delta));
+                error_locator = new_locator;
+            }
+            error_locator = poly_add(&error_locator, &poly_scale(&old_locator, delta));
+        }
+    }
+    while error_locator.first() == Some(&0) {
+        error_locator.remove(0);
+    }
+
+    let error_count = error_locator.len() - 1;
+    if error_count * 2 > parity_symbols {
+        return None;
+    }
+
+    // Chien search
+    let reversed: Vec<u8> = error_locator.iter().rev().copied().collect();
+    let positions: Vec<usize> = (0..codeword.len())
+        .filter(|i| poly_eval(&reversed, gf_pow2(*i)) == 0)
+        .map(|i| codeword.len() - 1 - i)
+        .collect();
+    if positions.len() != error_count {
+        return None;
+    }
+
+    // Forney
+    let coefficient_positions: Vec<usize> =
+        positions.iter().map(|p| codeword.len() - 1 - p).collect();
+    let errata_locator = coefficient_positions
+        .iter()
+        .fold(vec![1], |loc, p| poly_mul(&loc, &[gf_pow2(*p), 1]));
+
+    // The trailing zero shifts the evaluator to match the first root being α^0
+    let reversed_syndromes: Vec<u8> = syndromes.iter().rev().copied().chain([0]).collect();
+    let product = poly_mul(&reversed_syndromes, &errata_locator);
+    let evaluator_len = errata_locator.len();
+    let evaluator = &product[product.len() - evaluator_len..];
+
+    let locations: Vec<u8> = coefficient_positions.iter().map(|p| gf_pow2(*p)).collect();
+    let mut corrected = codeword.to_vec();
+    for (i, location) in locations.iter().enumerate() {
+        let location_inverse = gf_inverse(*location);
+
+        let locator_derivative = locations
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |acc, (_, other)| {
+                gf_mul(acc, 1 ^ gf_mul(location_inverse, *other))
+            });
+        if locator_derivative == 0 {
+            return None;
+        }
+
+        let y = gf_mul(*location, poly_eval(evaluator, location_inverse));
+        corrected[positions[i]] ^= gf_div(y, locator_derivative);
+    }
+
+    let still_bad = (0..parity_symbols).any(|i| poly_eval(&corrected, gf_pow2(i)) != 0);
+    if still_bad {
+        return None;
+    }
+
+    Some(corrected)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn codeword_corrects_up_to_half_parity() {
+        let message: Vec<u8> = (0..100).collect();
+        let mut codeword = message.clone();
+        codeword.extend(rs_encode(&message, 10));
+
+        for position in [0, 7, 50, 99, 105] {
+            codeword[position] ^= 0x5A;
+        }
+
+        let fixed = rs_correct(&codeword, 10).unwrap();
+        assert_eq!(&fixed[..100], &message[..]);
+    }
+
+    #[test]
+    fn codeword_rejects_too_many_errors() {
+        let message: Vec<u8> = (0..100).collect();
+        let mut codeword = message.clone();
+        codeword.extend(rs_encode(&message, 4));
+
+        for position in [0, 7, 50] {
+            codeword[position] ^= 0x5A;
+        }
+
+        assert!(rs_correct(&codeword, 4).is_none_or(|fixed| fixed[..100] != message[..]));
+    }
+
+    #[test]
+    fn stream_round_trip_with_burst_errors() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let data: Vec<u8> = (0..2000).map(|_| rng.gen()).collect();
+
+        let mut stream = encode(&data, 400);
+        assert_eq!(stream.len(), encoded_len(data.len(), 400));
+        assert_eq!(parity_budget(&stream).unwrap(), 400);
+
+        // Two bursts the size of a mon and a damaged header copy
+        for offset in [100, 1500] {
+            for byte in &mut stream[offset..offset + 9] {
+                *byte = !*byte;
+            }
+        }
+        stream[2] ^= 0xFF;
+
+        let (decoded, corrected) = decode(&stream).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(corrected.len(), 18);
+        assert_eq!(corrected[0], (100, 0xFF));
+        assert_eq!(corrected[17], (1508, 0xFF));
+        assert_eq!(parity_budget(&stream).unwrap(), 400);
+    }
+
+    #[test]
+    fn no_parity_passes_data_through() {
+        let data = vec![1, 2, 3];
+        let (decoded, corrected) = decode(&encode(&data, 0)).unwrap();
+        assert_eq!(decoded, data);
+        assert!(corrected.is_empty());
+    }
+}
@@ -1,150 +1,660 @@
 use std::{
     fs::File,
-    io::{self, Read, Write},
-    path::PathBuf,
+    io::{Read, Write},
+    path::{Component, Path},
+    time::{Duration, UNIX_EPOCH},
 };
 
-use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 use serde::{Deserialize, Serialize};
 
-use crate::pc::PC;
+use crate::{
+    box_mon::MonLayout,
+    codec::{compress_smallest, Codec, CODEC_MASK},
+    container::{self, ContainerError},
+    crypto,
+    error::MonFsError,
+    fec,
+    pc::{
+        MonPosition, PC, PC_FLAG_ARCHIVE, PC_FLAG_BOX_DATA, PC_FLAG_CHECKSUM, PC_FLAG_ENCRYPTED,
+        PC_FLAG_FEC, PC_FLAG_LOG, PC_FLAG_STORAGE,
+    },
+    pc_set::{set_header, PcSetError},
+    profile::EncodingProfile,
+    storage::Storage,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PcFile {
+    // Path relative to the folder the files are written to, folders split by /
     pub name: String,
     pub attributes: u8,
     pub data: Vec<u8>,
+    // Unix permission bits
+    pub mode: Option<u32>,
+    // Seconds since the Unix epoch
+    pub modified: Option<i64>,
+    // CRC32 of the uncompressed data, None for files from version 0 PCs
+    pub hash: Option<u32>,
+}
+
+// What ls shows for a file, sizes are in bytes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PcFileInfo {
+    pub name: String,
+    pub size: usize,
+    pub stored_size: usize,
+    pub compressed: bool,
+    pub codec: Codec,
+    pub mode: Option<u32>,
+    pub modified: Option<i64>,
+    pub hash: Option<u32>,
+    // False when the data doesn't match the hash, true for files without one
+    pub crc_ok: bool,
+}
+
+// PcFile as written with bincode before format version 1, without paths or metadata
+#[derive(Deserialize)]
+struct PcFileV1 {
+    name: String,
+    attributes: u8,
+    data: Vec<u8>,
 }
 
-pub const COMPRESSION_LEVEL: Compression = Compression::best();
+impl From<PcFileV1> for PcFile {
+    fn from(value: PcFileV1) -> Self {
+        PcFile {
+            name: value.name,
+            attributes: value.attributes,
+            data: value.data,
+            mode: None,
+            modified: None,
+            hash: None,
+        }
+    }
+}
 
 impl PcFile {
-    pub fn new(name: &str, data: Vec<u8>) -> PcFile {
-        let mut e = ZlibEncoder::new(Vec::new(), COMPRESSION_LEVEL);
-        e.write_all(&data).unwrap();
-        let compressed_data = e.finish().unwrap();
+    pub fn new(name: &str, data: Vec<u8>) -> Result<PcFile, MonFsError> {
+        check_path(name)?;
 
-        let compressed = compressed_data.len() < data.len();
+        let hash = crc32fast::hash(&data);
+        let (codec, data) = compress_smallest(&data)?;
 
-        PcFile {
+        Ok(PcFile {
             name: name.to_string(),
-            attributes: if compressed { 0x01 } else { 0x00 },
-            data: if compressed { compressed_data } else { data },
-        }
+            attributes: codec as u8,
+            data,
+            mode: None,
+            modified: None,
+            hash: Some(hash),
+        })
+    }
+
+    pub fn codec(&self) -> Result<Codec, MonFsError> {
+        Codec::from_attributes(self.attributes)
     }
 
     pub fn is_compressed(&self) -> bool {
-        self.attributes & 0x01 == 0x01
+        self.attributes & CODEC_MASK != Codec::Stored as u8
     }
 
-    pub fn get_data(&self) -> Vec<u8> {
-        if self.is_compressed() {
-            let mut d = ZlibDecoder::new(&self.data[..]);
-            let mut data = Vec::new();
-            d.read_to_end(&mut data).unwrap();
-            data
-        } else {
-            self.data.clone()
+    // Errors instead of handing back data that doesn't match the file's hash
+    pub fn get_data(&self) -> Result<Vec<u8>, MonFsError> {
+        let data = self.codec()?.decompress(&self.data)?;
+        if !self.hash_matches(&data) {
+            return Err(MonFsError::HashMismatch(self.name.clone()));
         }
+        Ok(data)
+    }
+
+    fn hash_matches(&self, data: &[u8]) -> bool {
+        self.hash.is_none_or(|hash| crc32fast::hash(data) == hash)
     }
 
-    pub fn write_to_folder(&self, folder: &PathBuf) {
-        // This is shit but I don't care
-        let data = self.get_data();
+    // A hash mismatch is reported in crc_ok so one bad file doesn't stop the others being listed
+    pub fn info(&self) -> Result<PcFileInfo, MonFsError> {
+        let data = self.codec()?.decompress(&self.data)?;
+        Ok(PcFileInfo {
+            name: self.name.clone(),
+            size: data.len(),
+            stored_size: self.data.len(),
+            compressed: self.is_compressed(),
+            codec: self.codec()?,
+            mode: self.mode,
+            modified: self.modified,
+            hash: self.hash,
+            crc_ok: self.hash_matches(&data),
+        })
+    }
 
-        let file_path = folder.clone().join(&self.name);
-        let mut file = if file_path.exists() {
-            std::fs::File::open(file_path).unwrap()
-        } else {
-            std::fs::File::create(file_path).unwrap()
-        };
-        file.write_all(&data).unwrap();
+    fn set_metadata(&mut self, metadata: &std::fs::Metadata) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            self.mode = Some(metadata.permissions().mode() & MODE_MASK);
+        }
+        self.modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs() as i64);
+    }
+
+    // Creates any folders in the path, names from a PC are checked again since the PC could
+    // have come from anywhere
+    pub fn write_to_folder(&self, folder: &Path) -> Result<(), MonFsError> {
+        check_path(&self.name)?;
+        let data = self.get_data()?;
+
+        let path = folder.join(&self.name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // The time is set through the handle written with and the mode last, so read only
+        // modes don't lock the file before it's finished
+        let mut file = File::create(&path)?;
+        file.write_all(&data)?;
+        if let Some(modified) = self.modified {
+            let time = match modified >= 0 {
+                true => UNIX_EPOCH + Duration::from_secs(modified as u64),
+                false => UNIX_EPOCH - Duration::from_secs(modified.unsigned_abs()),
+            };
+            file.set_modified(time)?;
+        }
+        #[cfg(unix)]
+        if let Some(mode) = self.mode {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(mode & MODE_MASK))?;
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Only the permission bits, a PC from a screenshot can't hand out setuid, setgid or sticky bits
+pub const MODE_MASK: u32 = 0o777;
+
+// Only plain relative paths, nothing that could climb out of the folder being written to
+fn check_path(name: &str) -> Result<(), MonFsError> {
+    let path = Path::new(name);
+    let plain = !name.is_empty()
+        && !name.contains('\\')
+        && name.split('/').all(|part| !part.is_empty())
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !plain {
+        return Err(MonFsError::BadPath(name.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct PcEncodeOptions {
+    // Mons spent on Reed-Solomon parity so misread mons can be corrected
+    pub parity_mons: usize,
+    // Reserve bits in every mon for a checksum so bad mons can be found
+    pub mon_checksums: bool,
+    pub layout: MonLayout,
+    pub compress_files: bool,
+    // Start the data in the box titles and wallpapers
+    pub box_data: bool,
+    // Write each file as its own record so adding files leaves the mons already caught alone.
+    // Encryption seals everything under a fresh nonce each time so it rewrites every mon anyway
+    pub append_log: bool,
+    // Compress all the files together as one stream instead of one by one, small files share
+    // what they have in common. Takes over from append_log
+    pub archive: bool,
+    // Encrypt everything after compression, the files can't be read without it
+    pub password: Option<String>,
+    // Recorded in the header instead of the layout when set
+    pub profile: Option<&'static EncodingProfile>,
+}
+
+impl Default for PcEncodeOptions {
+    fn default() -> Self {
+        PcEncodeOptions {
+            parity_mons: 0,
+            mon_checksums: false,
+            layout: MonLayout::default(),
+            compress_files: true,
+            box_data: false,
+            append_log: false,
+            archive: false,
+            password: None,
+            profile: None,
+        }
+    }
+}
+
+// Profiles don't cover the log, archive or password, they can be set on top afterwards
+impl From<&'static EncodingProfile> for PcEncodeOptions {
+    fn from(profile: &'static EncodingProfile) -> Self {
+        PcEncodeOptions {
+            parity_mons: profile.parity_mons,
+            mon_checksums: profile.mon_checksums,
+            layout: profile.layout,
+            compress_files: profile.compress_files,
+            box_data: profile.box_data,
+            profile: Some(profile),
+            ..Default::default()
+        }
+    }
+}
+
+impl PcEncodeOptions {
+    // The options pc was written with so editing it keeps them. The password isn't stored,
+    // it has to be set again for encrypted PCs
+    pub fn from_pc(pc: &PC) -> Result<PcEncodeOptions, MonFsError> {
+        let flags = pc.get_flags()?;
+        let profile = pc.get_profile()?;
+
+        Ok(PcEncodeOptions {
+            parity_mons: pc.parity_mons()?,
+            mon_checksums: flags & PC_FLAG_CHECKSUM != 0,
+            layout: pc.get_layout()?,
+            compress_files: profile.is_none_or(|profile| profile.compress_files),
+            box_data: flags & PC_FLAG_BOX_DATA != 0,
+            append_log: flags & PC_FLAG_LOG != 0,
+            archive: flags & PC_FLAG_ARCHIVE != 0,
+            password: None,
+            profile,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct FecReport {
+    pub corrected: Vec<MonPosition>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FilePc {
     pub files: Vec<PcFile>,
 }
 
 impl FilePc {
     pub fn new() -> Self {
-        FilePc { files: Vec::new() }
+        Self::default()
+    }
+
+    pub fn new_from_pc(pc: PC) -> Result<Self, MonFsError> {
+        Ok(Self::new_from_pc_with_report(pc)?.0)
+    }
+
+    // Also reports which mons had to be corrected when the PC was written with parity
+    pub fn new_from_pc_with_report(pc: PC) -> Result<(Self, FecReport), MonFsError> {
+        Self::new_from_storage_with_report(Storage::new(pc))
+    }
+
+    pub fn new_from_storage(storage: Storage) -> Result<Self, MonFsError> {
+        Ok(Self::new_from_storage_with_report(storage)?.0)
+    }
+
+    pub fn new_from_storage_with_report(storage: Storage) -> Result<(Self, FecReport), MonFsError> {
+        Self::new_from_storage_with_password(storage, None)
+    }
+
+    pub fn new_from_pc_with_password(pc: PC, password: &str) -> Result<Self, MonFsError> {
+        Ok(Self::new_from_storage_with_password(Storage::new(pc), Some(password))?.0)
     }
 
-    pub fn new_from_pc(mut pc: PC) -> Option<Self> {
+    // Without a password an encrypted PC gives PasswordNeeded
+    pub fn new_from_storage_with_password(
+        mut storage: Storage,
+        password: Option<&str>,
+    ) -> Result<(Self, FecReport), MonFsError> {
+        if let Some(header) = set_header(&storage.pc)? {
+            return Err(PcSetError::PartOfSet {
+                sequence: header.sequence,
+                total: header.total,
+            }
+            .into());
+        }
+
         let mut buf = Vec::new();
-        if pc.read_to_end(&mut buf).is_err() {
-            return None;
+        storage.read_to_end(&mut buf)?;
+
+        let mut report = FecReport::default();
+        if storage.pc.get_flags()? & PC_FLAG_FEC != 0 {
+            let (data, corrected) = fec::decode(&buf)?;
+            for (offset, flipped) in corrected {
+                for bit in (0..8).filter(|bit| flipped & (1 << bit) != 0) {
+                    // Only mons are reported, bits corrected elsewhere are left out
+                    let position = match storage.mon_position_for_bit(offset * 8 + bit)? {
+                        Some(position) => position,
+                        None => continue,
+                    };
+                    if !report.corrected.contains(&position) {
+                        report.corrected.push(position);
+                    }
+                }
+            }
+            buf = data;
         }
-        match bincode::deserialize(&buf) {
-            Ok(file_pc) => Some(file_pc),
-            Err(_) => None,
+
+        let file_pc = Self::deserialize(&buf, &storage.pc, password)?;
+        Ok((file_pc, report))
+    }
+
+    // Reads the files the way the PC's format version wrote them
+    pub(crate) fn deserialize(
+        data: &[u8],
+        pc: &PC,
+        password: Option<&str>,
+    ) -> Result<Self, MonFsError> {
+        if pc.get_flags()? & PC_FLAG_ENCRYPTED != 0 {
+            let password = password.ok_or(MonFsError::PasswordNeeded)?;
+            let mut decrypted = pc.clone();
+            decrypted.set_flags(decrypted.get_flags()? & !PC_FLAG_ENCRYPTED)?;
+            return Self::deserialize(&crypto::decrypt(data, password)?, &decrypted, None);
         }
+
+        if pc.get_flags()? & PC_FLAG_ARCHIVE != 0 {
+            let (&id, archive) =
+                data.split_first()
+                    .ok_or(MonFsError::Container(ContainerError::Truncated {
+                        offset: 0,
+                    }))?;
+            let codec = Codec::from_repr(id).ok_or(MonFsError::UnknownCodec(id))?;
+            let mut unpacked = pc.clone();
+            unpacked.set_flags(unpacked.get_flags()? & !PC_FLAG_ARCHIVE)?;
+            return Self::deserialize(&codec.decompress(archive)?, &unpacked, None);
+        }
+
+        if pc.get_version()? == 0 {
+            let files: Vec<PcFileV1> = bincode::deserialize(data)?;
+            return Ok(FilePc {
+                files: files.into_iter().map(PcFile::from).collect(),
+            });
+        }
+
+        Ok(FilePc {
+            files: container::read_files(data)?,
+        })
     }
 
-    pub fn add_file(&mut self, name: &str, source: &PathBuf) -> Result<(), io::Error> {
-        let data = match File::open(source) {
-            Ok(mut file) => {
-                let mut buf = Vec::new();
-                if let Err(err) = file.read_to_end(&mut buf) {
-                    return Err(err);
-                }
-                buf
+    // Also keeps the file's permissions and modification time
+    pub fn add_file(&mut self, name: &str, source: &Path) -> Result<(), MonFsError> {
+        let mut buf = Vec::new();
+        let mut file = File::open(source)?;
+        file.read_to_end(&mut buf)?;
+        self.add_file_raw(name, buf)?;
+
+        self.files
+            .last_mut()
+            .unwrap()
+            .set_metadata(&file.metadata()?);
+
+        Ok(())
+    }
+
+    // Like replace_file with the mode and modified time taken from the source
+    pub fn replace_file_from(&mut self, name: &str, source: &Path) -> Result<(), MonFsError> {
+        let mut buf = Vec::new();
+        let mut file = File::open(source)?;
+        file.read_to_end(&mut buf)?;
+        self.replace_file(name, buf)?;
+
+        let index = self.file_index(name)?;
+        self.files[index].set_metadata(&file.metadata()?);
+
+        Ok(())
+    }
+
+    // Adds every file under the folder, named by their path relative to it
+    pub fn add_folder(&mut self, folder: &Path) -> Result<(), MonFsError> {
+        self.add_folder_under("", folder)
+    }
+
+    fn add_folder_under(&mut self, prefix: &str, folder: &Path) -> Result<(), MonFsError> {
+        let mut entries = std::fs::read_dir(folder)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            let path = entry.path();
+            // Linked folders are skipped so a link back up the tree can't loop forever
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.add_folder_under(&format!("{}/", name), &path)?;
+            } else if path.is_file() {
+                self.add_file(&name, &path)?;
             }
-            Err(err) => return Err(err),
-        };
+        }
 
-        self.add_file_raw(name, data)
+        Ok(())
     }
 
-    pub fn add_file_raw(&mut self, name: &str, data: Vec<u8>) -> Result<(), io::Error> {
+    pub fn add_file_raw(&mut self, name: &str, data: Vec<u8>) -> Result<(), MonFsError> {
         if self.files.iter().any(|f| f.name == name) {
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("File with name {} already exists", name),
-            ));
+            return Err(MonFsError::FileExists(name.to_string()));
+        }
+
+        self.files.push(PcFile::new(name, data)?);
+
+        Ok(())
+    }
+
+    pub fn remove_file(&mut self, name: &str) -> Result<PcFile, MonFsError> {
+        let index = self.file_index(name)?;
+
+        Ok(self.files.remove(index))
+    }
+
+    pub fn rename_file(&mut self, from: &str, to: &str) -> Result<(), MonFsError> {
+        check_path(to)?;
+        let index = self.file_index(from)?;
+        if from != to && self.files.iter().any(|f| f.name == to) {
+            return Err(MonFsError::FileExists(to.to_string()));
         }
 
-        self.files.push(PcFile::new(name, data));
+        self.files[index].name = to.to_string();
 
         Ok(())
     }
 
-    pub fn write_to_folder(&self, folder: &PathBuf) {
+    // Swaps in new data for the file keeping its place, mode and modified time, adds it when
+    // there isn't one yet
+    pub fn replace_file(&mut self, name: &str, data: Vec<u8>) -> Result<(), MonFsError> {
+        let mut file = PcFile::new(name, data)?;
+        match self.files.iter().position(|f| f.name == name) {
+            Some(index) => {
+                file.mode = self.files[index].mode;
+                file.modified = self.files[index].modified;
+                self.files[index] = file;
+            }
+            None => self.files.push(file),
+        }
+
+        Ok(())
+    }
+
+    pub fn file_infos(&self) -> Result<Vec<PcFileInfo>, MonFsError> {
+        self.files.iter().map(PcFile::info).collect()
+    }
+
+    fn file_index(&self, name: &str) -> Result<usize, MonFsError> {
+        self.files
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or_else(|| MonFsError::FileNotFound(name.to_string()))
+    }
+
+    pub fn write_to_folder(&self, folder: &Path) -> Result<(), MonFsError> {
         if !folder.exists() {
-            std::fs::create_dir_all(folder).unwrap();
+            std::fs::create_dir_all(folder)?;
         }
 
         if !folder.is_dir() {
-            panic!("Must be a folder");
+            return Err(MonFsError::NotAFolder(folder.to_path_buf()));
         }
 
         for file in &self.files {
-            file.write_to_folder(folder);
+            file.write_to_folder(folder)?;
         }
+
+        Ok(())
+    }
+
+    pub fn as_pc(&self) -> Result<PC, MonFsError> {
+        self.as_pc_with_options(&PcEncodeOptions::default())
     }
 
-    pub fn as_pc(&self) -> Result<PC, std::io::Error> {
-        let encoded = bincode::serialize(&self).unwrap();
+    pub fn as_pc_with_options(&self, options: &PcEncodeOptions) -> Result<PC, MonFsError> {
+        let (mut pc, encoded) = self.encode_with_options(options)?;
+        pc.write_all(&encoded)?;
 
-        let mut pc = PC::new();
-        match pc.write_all(&encoded) {
-            Ok(_) => Ok(pc),
-            Err(err) => Err(err),
+        Ok(pc)
+    }
+
+    // Like as_pc_with_options but data that doesn't fit in the PC carries on into the party
+    // and item PC
+    pub fn as_storage_with_options(
+        &self,
+        options: &PcEncodeOptions,
+    ) -> Result<Storage, MonFsError> {
+        let (mut pc, encoded) = self.encode_with_options(options)?;
+        pc.set_flags(pc.get_flags()? | PC_FLAG_STORAGE)?;
+
+        let mut storage = Storage::new(pc);
+        storage.write_all(&encoded)?;
+
+        Ok(storage)
+    }
+
+    // An empty PC with the header set up and the bytes to write to it
+    fn encode_with_options(&self, options: &PcEncodeOptions) -> Result<(PC, Vec<u8>), MonFsError> {
+        let mut pc = empty_pc_with_options(options)?;
+        let encoded = self.serialize_with_options(options, &mut pc)?;
+        Ok((pc, protect(encoded, options)))
+    }
+
+    // The archive codec is recorded in the header of pc
+    pub(crate) fn serialize_with_options(
+        &self,
+        options: &PcEncodeOptions,
+        pc: &mut PC,
+    ) -> Result<Vec<u8>, MonFsError> {
+        if let Some(password) = &options.password {
+            let plain = PcEncodeOptions {
+                password: None,
+                ..options.clone()
+            };
+            let data = self.serialize_with_options(&plain, pc)?;
+            pc.set_flags(pc.get_flags()? | PC_FLAG_ENCRYPTED)?;
+            return crypto::encrypt(&data, password);
         }
+
+        if options.archive {
+            let stored = PcEncodeOptions {
+                compress_files: false,
+                append_log: false,
+                archive: false,
+                ..options.clone()
+            };
+            let (codec, data) = compress_smallest(&self.serialize_with_options(&stored, pc)?)?;
+            pc.set_flags(pc.get_flags()? | PC_FLAG_ARCHIVE)?;
+            // The codec goes in front of the archive, the header mon has no room left for it
+            let mut archive = vec![codec as u8];
+            archive.extend(data);
+            return Ok(archive);
+        }
+
+        let mut decompressed = vec![];
+        if !options.compress_files {
+            for file in &self.files {
+                decompressed.push(PcFile {
+                    name: file.name.clone(),
+                    attributes: file.attributes & !CODEC_MASK,
+                    data: file.get_data()?,
+                    mode: file.mode,
+                    modified: file.modified,
+                    hash: file.hash,
+                });
+            }
+        }
+        let files = match options.compress_files {
+            true => &self.files,
+            false => &decompressed,
+        };
+
+        // Records are appended one after another so append_log needs nothing special
+        Ok(container::write_files(files))
+    }
+}
+
+pub(crate) fn empty_pc_with_options(options: &PcEncodeOptions) -> Result<PC, MonFsError> {
+    let mut flags = 0;
+    if options.mon_checksums {
+        flags |= PC_FLAG_CHECKSUM;
+    }
+    if options.box_data {
+        flags |= PC_FLAG_BOX_DATA;
+    }
+    if options.parity_mons > 0 {
+        flags |= PC_FLAG_FEC;
+    }
+    if options.append_log && !options.archive {
+        flags |= PC_FLAG_LOG;
+    }
+
+    let mut pc = PC::new();
+    match options.profile {
+        Some(profile) => pc.set_profile(profile)?,
+        None => pc.set_layout(&options.layout)?,
+    }
+    // After the profile so options layered on top of it aren't reset
+    pc.set_flags(flags)?;
+
+    Ok(pc)
+}
+
+// Wraps the data in Reed-Solomon parity when the options ask for it
+pub(crate) fn protect(data: Vec<u8>, options: &PcEncodeOptions) -> Vec<u8> {
+    match options.parity_mons {
+        0 => data,
+        parity_mons => fec::encode(&data, parity_bytes(options, parity_mons)),
     }
 }
 
-impl Into<PC> for FilePc {
-    fn into(self) -> PC {
-        self.as_pc().unwrap()
+pub(crate) fn protected_len(data_len: usize, options: &PcEncodeOptions) -> usize {
+    match options.parity_mons {
+        0 => data_len,
+        parity_mons => fec::encoded_len(data_len, parity_bytes(options, parity_mons)),
     }
 }
 
-impl From<PC> for FilePc {
-    fn from(value: PC) -> Self {
-        Self::new_from_pc(value).unwrap()
+fn parity_bytes(options: &PcEncodeOptions, parity_mons: usize) -> usize {
+    parity_mons * options.layout.bit_count() / 8
+}
+
+// Undoes parity_bytes, exact since every mon holds more than a byte
+pub(crate) fn parity_mons_for_bytes(layout: &MonLayout, parity_bytes: usize) -> usize {
+    (parity_bytes * 8).div_ceil(layout.bit_count())
+}
+
+impl TryFrom<&FilePc> for PC {
+    type Error = MonFsError;
+
+    fn try_from(value: &FilePc) -> Result<Self, Self::Error> {
+        value.as_pc()
+    }
+}
+
+impl TryFrom<FilePc> for PC {
+    type Error = MonFsError;
+
+    fn try_from(value: FilePc) -> Result<Self, Self::Error> {
+        value.as_pc()
+    }
+}
+
+impl TryFrom<PC> for FilePc {
+    type Error = MonFsError;
+
+    fn try_from(value: PC) -> Result<Self, Self::Error> {
+        Self::new_from_pc(value)
     }
 }
 
@@ -152,10 +662,237 @@ impl From<PC> for FilePc {
 mod test {
     use std::{fs::File, path::PathBuf};
 
-    use crate::{mon_field::ByteCount, pc::PC};
+    use crate::{
+        box_mon::{BoxMon, MonLayout, MonPacking},
+        mon_field::ByteCount,
+        mon_species::BoxMonSpecies,
+        pc::{
+            MonPosition, HEADER_VERSION, PC, PC_BOX_SIZE, PC_FLAG_ARCHIVE, PC_FLAG_CHECKSUM,
+            PC_FLAG_FEC, PC_FLAG_LOG,
+        },
+    };
+    use std::time::{Duration, UNIX_EPOCH};
     use tempdir::TempDir;
 
-    use super::FilePc;
+    use super::{FilePc, PcEncodeOptions, PcFile};
+    use crate::codec::Codec;
+    use crate::error::MonFsError;
+    use crate::profile::PROFILES;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use std::io::Write;
+
+    #[test]
+    fn correct_misread_mons_with_parity() {
+        let mut file_pc = FilePc::new();
+        file_pc
+            .add_file_raw(
+                "ricky.webp",
+                include_bytes!("../../test_assets/ricky.webp").to_vec(),
+            )
+            .unwrap();
+
+        let options = PcEncodeOptions {
+            parity_mons: 60,
+            mon_checksums: true,
+            ..Default::default()
+        };
+        let mut pc = file_pc.as_pc_with_options(&options).unwrap();
+
+        // Swap the species of a couple of mons as if they were misread
+        for (box_index, slot) in [(1, 3), (4, 17)] {
+            let mut mon: BoxMon = pc.mons[box_index * 30 + slot].unwrap();
+            mon.species = BoxMonSpecies::from_repr(mon.species as u8 ^ 1).unwrap();
+            pc.set_mon(box_index, slot, mon);
+        }
+
+        let misread = vec![
+            MonPosition {
+                box_index: 1,
+                slot: 3,
+            },
+            MonPosition {
+                box_index: 4,
+                slot: 17,
+            },
+        ];
+        assert_eq!(pc.verify().unwrap().suspect, misread);
+
+        let (decoded, report) = FilePc::new_from_pc_with_report(pc).unwrap();
+        assert_eq!(
+            decoded.files[0].get_data().unwrap(),
+            file_pc.files[0].get_data().unwrap()
+        );
+        assert_eq!(report.corrected, misread);
+    }
+
+    #[test]
+    fn parity_mons_are_read_back_for_the_next_encode() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a.txt", vec![b'a'; 300]).unwrap();
+
+        let layouts = [
+            MonLayout::default(),
+            MonLayout {
+                packing: MonPacking::MixedRadix(30),
+                captured_ball: true,
+                ..Default::default()
+            },
+        ];
+        for layout in layouts {
+            for parity_mons in [0, 1, 7, 40, 150] {
+                let options = PcEncodeOptions {
+                    parity_mons,
+                    layout,
+                    ..Default::default()
+                };
+                let pc = file_pc.as_pc_with_options(&options).unwrap();
+                assert_eq!(pc.parity_mons().unwrap(), parity_mons);
+                FilePc::new_from_pc(pc).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn every_profile_round_trips() {
+        let mut file_pc = FilePc::new();
+        file_pc
+            .add_file_raw(
+                "ricky.webp",
+                include_bytes!("../../test_assets/ricky.webp").to_vec(),
+            )
+            .unwrap();
+        file_pc.add_file_raw("text", vec![b'a'; 300]).unwrap();
+
+        for profile in PROFILES.iter() {
+            let pc = file_pc.as_pc_with_options(&profile.into()).unwrap();
+            assert_eq!(pc.get_profile().unwrap(), Some(profile));
+
+            let decoded = FilePc::try_from(pc).unwrap();
+            for (decoded, original) in decoded.files.iter().zip(&file_pc.files) {
+                assert_eq!(decoded.get_data().unwrap(), original.get_data().unwrap());
+            }
+        }
+
+        let options = PcEncodeOptions {
+            compress_files: false,
+            ..Default::default()
+        };
+        let decoded = FilePc::try_from(file_pc.as_pc_with_options(&options).unwrap()).unwrap();
+        assert!(!decoded.files[1].is_compressed());
+        assert_eq!(decoded.files[1].data, vec![b'a'; 300]);
+    }
+
+    #[test]
+    fn options_layer_on_top_of_a_profile() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("text", vec![b'a'; 300]).unwrap();
+
+        let options = PcEncodeOptions {
+            parity_mons: 5,
+            mon_checksums: true,
+            append_log: true,
+            ..PcEncodeOptions::from(&PROFILES[0])
+        };
+        let pc = file_pc.as_pc_with_options(&options).unwrap();
+        assert_eq!(pc.get_profile().unwrap(), Some(&PROFILES[0]));
+        assert_eq!(
+            pc.get_flags().unwrap(),
+            PC_FLAG_FEC | PC_FLAG_CHECKSUM | PC_FLAG_LOG
+        );
+        assert_eq!(pc.parity_mons().unwrap(), 5);
+
+        let decoded = FilePc::try_from(pc).unwrap();
+        assert_eq!(decoded.files[0].get_data().unwrap(), vec![b'a'; 300]);
+    }
+
+    #[test]
+    fn edits_keep_the_options_the_pc_was_written_with() {
+        let option_sets = [
+            PcEncodeOptions {
+                box_data: true,
+                append_log: true,
+                password: Some("hunter2".to_string()),
+                ..PcEncodeOptions::from(&PROFILES[1])
+            },
+            PcEncodeOptions {
+                parity_mons: 10,
+                archive: true,
+                ..PcEncodeOptions::from(&PROFILES[2])
+            },
+        ];
+        type Edit = fn(&mut FilePc);
+        let edits: [Edit; 3] = [
+            |file_pc| drop(file_pc.remove_file("a.txt").unwrap()),
+            |file_pc| file_pc.rename_file("a.txt", "c.txt").unwrap(),
+            |file_pc| file_pc.replace_file("a.txt", vec![3; 200]).unwrap(),
+        ];
+
+        for options in option_sets {
+            let mut file_pc = FilePc::new();
+            file_pc.add_file_raw("a.txt", vec![1; 200]).unwrap();
+            file_pc.add_file_raw("b.txt", vec![2; 200]).unwrap();
+            let pc = file_pc.as_pc_with_options(&options).unwrap();
+
+            for edit in edits {
+                let mut edited = match &options.password {
+                    Some(password) => FilePc::new_from_pc_with_password(pc.clone(), password),
+                    None => FilePc::new_from_pc(pc.clone()),
+                }
+                .unwrap();
+                edit(&mut edited);
+
+                let mut read_back = PcEncodeOptions::from_pc(&pc).unwrap();
+                read_back.password = options.password.clone();
+                let edited_pc = edited.as_pc_with_options(&read_back).unwrap();
+                assert_eq!(edited_pc.get_flags().unwrap(), pc.get_flags().unwrap());
+                assert_eq!(edited_pc.get_profile().unwrap(), options.profile);
+                assert_eq!(edited_pc.parity_mons().unwrap(), options.parity_mons);
+
+                let decoded = match &options.password {
+                    Some(password) => FilePc::new_from_pc_with_password(edited_pc, password),
+                    None => FilePc::new_from_pc(edited_pc),
+                }
+                .unwrap();
+                assert_eq!(decoded.files.len(), edited.files.len());
+                for (decoded, edited) in decoded.files.iter().zip(&edited.files) {
+                    assert_eq!(decoded.name, edited.name);
+                    assert_eq!(decoded.get_data().unwrap(), edited.get_data().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors_instead_of_panicking() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a", vec![1; 10]).unwrap();
+        assert!(matches!(
+            file_pc.add_file_raw("a", vec![]),
+            Err(MonFsError::FileExists(_))
+        ));
+
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let random: Vec<u8> = (0..PC::byte_count() * 2).map(|_| rng.gen()).collect();
+        let mut too_big = FilePc::new();
+        too_big.add_file_raw("big", random).unwrap();
+        assert!(matches!(
+            PC::try_from(&too_big),
+            Err(MonFsError::CapacityExceeded { .. })
+        ));
+
+        // Header claiming more padding than a mon holds
+        let mut pc = file_pc.as_pc().unwrap();
+        pc.set_padding_amount(200).unwrap();
+        assert!(matches!(
+            FilePc::new_from_pc(pc),
+            Err(MonFsError::InvalidPaddingMon)
+        ));
+
+        let mut pc = PC::new();
+        pc.write_all(&[0xFF; 32]).unwrap();
+        assert!(matches!(FilePc::try_from(pc), Err(MonFsError::Bincode(_))));
+    }
 
     #[test]
     fn test_copy_file_into_pc() {
@@ -185,14 +922,14 @@ mod test {
         file_pc.add_file("ricky.webp", &pic_file_path).unwrap();
         file_pc.add_file("song.opus", &song_file_path).unwrap();
 
-        let pc: PC = file_pc.into();
+        let pc: PC = file_pc.try_into().unwrap();
 
-        let file_pc: FilePc = pc.into();
+        let file_pc: FilePc = pc.try_into().unwrap();
 
         let out_dir = TempDir::new("test_copy_file_into_pc_out").unwrap();
         {
             let path: PathBuf = out_dir.path().into();
-            file_pc.write_to_folder(&path);
+            file_pc.write_to_folder(&path).unwrap();
 
             let ricky = std::fs::read(path.join("ricky.webp")).unwrap();
             assert_eq!(
@@ -207,4 +944,340 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn append_log_only_adds_mons() {
+        let mut file_pc = FilePc::new();
+        file_pc
+            .add_file_raw(
+                "ricky.webp",
+                include_bytes!("../../test_assets/ricky.webp").to_vec(),
+            )
+            .unwrap();
+
+        for packing in [MonPacking::Bits, MonPacking::MixedRadix(30)] {
+            let options = PcEncodeOptions {
+                layout: MonLayout {
+                    packing,
+                    ..Default::default()
+                },
+                append_log: true,
+                ..Default::default()
+            };
+            let before = file_pc.as_pc_with_options(&options).unwrap();
+
+            let mut bigger = FilePc::new_from_pc(before.clone()).unwrap();
+            bigger.add_file_raw("text", vec![b'a'; 300]).unwrap();
+            let after = bigger.as_pc_with_options(&options).unwrap();
+
+            let decoded = FilePc::new_from_pc(after.clone()).unwrap();
+            assert_eq!(decoded.files.len(), 2);
+            assert_eq!(decoded.files[1].get_data().unwrap(), vec![b'a'; 300]);
+
+            // Besides the header only the last run and the new mons change
+            let used = before.mons.iter().skip(1).flatten().count();
+            let diff = before.diff(&after);
+            assert!(diff.boxes.is_empty());
+            assert!(diff.mons.iter().all(|position| {
+                let index = position.box_index * PC_BOX_SIZE + position.slot;
+                index == 0 || index > used - 30
+            }));
+        }
+    }
+
+    #[test]
+    fn folders_round_trip_with_metadata() {
+        let source = TempDir::new("mon-fs-source").unwrap();
+        std::fs::create_dir_all(source.path().join("docs/notes")).unwrap();
+        std::fs::write(source.path().join("top.txt"), b"top").unwrap();
+        std::fs::write(source.path().join("docs/notes/deep.txt"), vec![3; 100]).unwrap();
+        let deep = File::options()
+            .write(true)
+            .open(source.path().join("docs/notes/deep.txt"))
+            .unwrap();
+        deep.set_modified(UNIX_EPOCH + Duration::from_secs(1_000_000))
+            .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                source.path().join("top.txt"),
+                std::fs::Permissions::from_mode(0o100600),
+            )
+            .unwrap();
+        }
+
+        let mut file_pc = FilePc::new();
+        file_pc.add_folder(source.path()).unwrap();
+        let names: Vec<&str> = file_pc.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["docs/notes/deep.txt", "top.txt"]);
+
+        let decoded = FilePc::new_from_pc(file_pc.as_pc().unwrap()).unwrap();
+        let target = TempDir::new("mon-fs-target").unwrap();
+        decoded.write_to_folder(target.path()).unwrap();
+
+        let deep_path = target.path().join("docs/notes/deep.txt");
+        assert_eq!(std::fs::read(&deep_path).unwrap(), vec![3; 100]);
+        assert_eq!(
+            std::fs::metadata(&deep_path).unwrap().modified().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_000_000)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(target.path().join("top.txt")).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn read_only_files_keep_their_time_and_replacing_keeps_both() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("locked", vec![1; 10]).unwrap();
+        file_pc.files[0].mode = Some(0o100444);
+        file_pc.files[0].modified = Some(2_000_000);
+
+        let target = TempDir::new("mon-fs-target").unwrap();
+        file_pc.write_to_folder(target.path()).unwrap();
+        let metadata = std::fs::metadata(target.path().join("locked")).unwrap();
+        assert_eq!(
+            metadata.modified().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(2_000_000)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(metadata.permissions().mode() & 0o7777, 0o444);
+        }
+
+        file_pc.replace_file("locked", vec![2; 10]).unwrap();
+        assert_eq!(file_pc.files[0].mode, Some(0o100444));
+        assert_eq!(file_pc.files[0].modified, Some(2_000_000));
+
+        // Taking them from the source instead
+        let source = target.path().join("locked");
+        file_pc.files[0].mode = None;
+        file_pc.replace_file_from("locked", &source).unwrap();
+        assert_eq!(file_pc.files[0].get_data().unwrap(), vec![1; 10]);
+        assert_eq!(file_pc.files[0].modified, Some(2_000_000));
+        #[cfg(unix)]
+        assert_eq!(file_pc.files[0].mode, Some(0o444));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn setuid_bits_are_not_written() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("tool", vec![1; 10]).unwrap();
+        file_pc.files[0].mode = Some(0o4755);
+
+        let target = TempDir::new("mon-fs-target").unwrap();
+        file_pc.write_to_folder(target.path()).unwrap();
+        let source = target.path().join("tool");
+        let metadata = std::fs::metadata(&source).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o755);
+
+        std::fs::set_permissions(&source, std::fs::Permissions::from_mode(0o4755)).unwrap();
+        file_pc.replace_file_from("tool", &source).unwrap();
+        assert_eq!(file_pc.files[0].mode, Some(0o755));
+    }
+
+    #[test]
+    fn paths_leaving_the_folder_are_rejected() {
+        for name in ["../x", "/abs", "a/../../x", "a\\b", "a//b", ""] {
+            assert!(
+                matches!(PcFile::new(name, vec![1]), Err(MonFsError::BadPath(_))),
+                "{name}"
+            );
+        }
+
+        // A crafted PC can't get around the check
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("fine", vec![1]).unwrap();
+        file_pc.files[0].name = "../escaped".to_string();
+        let decoded = FilePc::new_from_pc(file_pc.as_pc().unwrap()).unwrap();
+        let target = TempDir::new("mon-fs-target").unwrap();
+        assert!(matches!(
+            decoded.write_to_folder(&target.path().join("inner")),
+            Err(MonFsError::BadPath(_))
+        ));
+        assert!(!target.path().join("escaped").exists());
+    }
+
+    #[test]
+    fn reads_files_from_version_0_pcs() {
+        let options = PcEncodeOptions::default();
+        let legacy = bincode::serialize(&vec![("old.txt".to_string(), 0u8, b"old".to_vec())]);
+
+        let mut pc = super::empty_pc_with_options(&options).unwrap();
+        pc.set_header_field(HEADER_VERSION, 0).unwrap();
+        pc.write_all(&super::protect(legacy.unwrap(), &options))
+            .unwrap();
+
+        let decoded = FilePc::new_from_pc(pc).unwrap();
+        assert_eq!(decoded.files.len(), 1);
+        assert_eq!(decoded.files[0].name, "old.txt");
+        assert_eq!(decoded.files[0].get_data().unwrap(), b"old");
+        assert_eq!(decoded.files[0].mode, None);
+    }
+
+    #[test]
+    fn remove_rename_and_replace_files() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a.txt", vec![1; 50]).unwrap();
+        file_pc.add_file_raw("b.txt", vec![2; 50]).unwrap();
+        file_pc.add_file_raw("c.txt", vec![3; 50]).unwrap();
+
+        assert_eq!(file_pc.remove_file("b.txt").unwrap().name, "b.txt");
+        assert!(matches!(
+            file_pc.remove_file("b.txt"),
+            Err(MonFsError::FileNotFound(_))
+        ));
+
+        assert!(matches!(
+            file_pc.rename_file("a.txt", "c.txt"),
+            Err(MonFsError::FileExists(_))
+        ));
+        assert!(matches!(
+            file_pc.rename_file("a.txt", "../a.txt"),
+            Err(MonFsError::BadPath(_))
+        ));
+        file_pc.rename_file("a.txt", "docs/a.txt").unwrap();
+
+        file_pc.replace_file("c.txt", vec![4; 10]).unwrap();
+        file_pc.replace_file("d.txt", vec![5; 10]).unwrap();
+
+        let decoded = FilePc::new_from_pc(file_pc.as_pc().unwrap()).unwrap();
+        let names: Vec<&str> = decoded.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["docs/a.txt", "c.txt", "d.txt"]);
+        assert_eq!(decoded.files[1].get_data().unwrap(), vec![4; 10]);
+    }
+
+    #[test]
+    fn file_infos_and_usage() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("text", vec![b'a'; 400]).unwrap();
+        let random: Vec<u8> = (0..40).map(|i| (i * 97 % 251) as u8).collect();
+        file_pc.add_file_raw("random", random).unwrap();
+
+        let infos = file_pc.file_infos().unwrap();
+        assert_eq!(infos[0].size, 400);
+        assert!(infos[0].compressed && infos[0].stored_size < 400);
+        assert_eq!(infos[1].size, 40);
+        assert!(!infos[1].compressed && infos[1].stored_size == 40);
+
+        let empty = FilePc::new().as_pc().unwrap().usage().unwrap();
+        let usage = file_pc.as_pc().unwrap().usage().unwrap();
+        assert!(usage.used_mons > empty.used_mons);
+        assert!(usage.remaining_bytes < empty.remaining_bytes);
+        assert_eq!(usage.data_mons, empty.data_mons);
+    }
+
+    #[test]
+    fn files_compressed_before_codecs_still_decode() {
+        let text = vec![b'a'; 300];
+        let file = super::PcFile {
+            name: "old.txt".to_string(),
+            attributes: 0x01,
+            data: Codec::Zlib.compress(&text).unwrap(),
+            mode: None,
+            modified: None,
+            hash: None,
+        };
+        assert_eq!(file.codec().unwrap(), Codec::Zlib);
+        assert_eq!(file.get_data().unwrap(), text);
+
+        let new = super::PcFile::new("new.txt", text.clone()).unwrap();
+        assert_eq!(new.codec().unwrap() as u8, new.attributes);
+        assert!(new.data.len() <= file.data.len());
+    }
+
+    #[test]
+    fn archive_shares_compression_between_files() {
+        let mut file_pc = FilePc::new();
+        for i in 0..12 {
+            let text = format!("[box {i}]\nname = \"mon-fs box {i}\"\nwallpaper = \"forest\"\n");
+            file_pc
+                .add_file_raw(&format!("boxes/{i}.toml"), text.into_bytes())
+                .unwrap();
+        }
+
+        let per_file = file_pc.as_pc().unwrap();
+        for append_log in [false, true] {
+            let options = PcEncodeOptions {
+                archive: true,
+                append_log,
+                ..Default::default()
+            };
+            let archived = file_pc.as_pc_with_options(&options).unwrap();
+            assert_ne!(archived.get_flags().unwrap() & PC_FLAG_ARCHIVE, 0);
+            assert!(archived.usage().unwrap().used_mons < per_file.usage().unwrap().used_mons);
+
+            let decoded = FilePc::new_from_pc(archived).unwrap();
+            assert_eq!(decoded.files.len(), 12);
+            for (decoded, original) in decoded.files.iter().zip(&file_pc.files) {
+                assert_eq!(decoded.name, original.name);
+                assert_eq!(decoded.get_data().unwrap(), original.get_data().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_files_fail_their_hash() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a.txt", vec![b'a'; 100]).unwrap();
+        file_pc.add_file_raw("b.bin", vec![1, 2, 3]).unwrap();
+
+        let mut decoded = FilePc::new_from_pc(file_pc.as_pc().unwrap()).unwrap();
+        assert_eq!(decoded.files[0].hash, Some(crc32fast::hash(&[b'a'; 100])));
+        assert_eq!(decoded.files[1].get_data().unwrap(), vec![1, 2, 3]);
+
+        // Stored uncompressed, so a flipped bit still decompresses
+        assert_eq!(decoded.files[1].codec().unwrap(), Codec::Stored);
+        decoded.files[1].data[0] ^= 0x10;
+        assert!(matches!(
+            decoded.files[1].get_data(),
+            Err(MonFsError::HashMismatch(name)) if name == "b.bin"
+        ));
+
+        let infos = decoded.file_infos().unwrap();
+        assert!(infos[0].crc_ok);
+        assert!(!infos[1].crc_ok);
+        assert_eq!(infos[1].size, 3);
+
+        decoded.files[1].hash = None;
+        assert_eq!(decoded.files[1].get_data().unwrap(), vec![0x11, 2, 3]);
+        assert!(decoded.files[1].info().unwrap().crc_ok);
+    }
+
+    #[test]
+    fn encrypted_pcs_need_the_password() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("diary.txt", vec![b'a'; 200]).unwrap();
+
+        for archive in [false, true] {
+            let options = PcEncodeOptions {
+                archive,
+                parity_mons: 20,
+                password: Some("hunter2".to_string()),
+                ..Default::default()
+            };
+            let pc = file_pc.as_pc_with_options(&options).unwrap();
+
+            assert!(matches!(
+                FilePc::new_from_pc(pc.clone()),
+                Err(MonFsError::PasswordNeeded)
+            ));
+            assert!(matches!(
+                FilePc::new_from_pc_with_password(pc.clone(), "hunter3"),
+                Err(MonFsError::WrongPassword)
+            ));
+
+            let decoded = FilePc::new_from_pc_with_password(pc, "hunter2").unwrap();
+            assert_eq!(decoded.files[0].name, "diary.txt");
+            assert_eq!(decoded.files[0].get_data().unwrap(), vec![b'a'; 200]);
+        }
+    }
 }
@@ -1,12 +1,26 @@
 pub mod box_mon;
+pub mod codec;
+pub mod container;
+pub mod crypto;
+pub mod error;
+pub mod fec;
 pub mod file_pc;
 pub mod mon_captured_ball;
 pub mod mon_field;
 pub mod mon_gender;
 pub mod mon_held_item;
+pub mod mon_markings;
 pub mod mon_name;
+pub mod mon_nature;
 pub mod mon_species;
 pub mod pc;
+pub mod pc_box;
+pub mod pc_set;
+pub mod plan;
+pub mod profile;
+pub mod reuse;
+pub mod save;
+pub mod storage;
 use bit_vec::BitVec;
 
 #[derive(Debug, Clone, Default)]
@@ -28,7 +42,7 @@ impl BoxMonBitVec {
         let value: u64 = value.into();
 
         for i in 0..size {
-            bit_vec.push(value & (1 << i as usize) != 0);
+            bit_vec.push(value & (1 << i) != 0);
         }
 
         BoxMonBitVec(bit_vec)
@@ -45,7 +59,7 @@ impl BoxMonBitVec {
     }
 
     pub fn to_raw(&self) -> Vec<u8> {
-        let mut raw = Vec::with_capacity((self.0.len() + 7) / 8);
+        let mut raw = Vec::with_capacity(self.0.len().div_ceil(8));
         let mut byte = 0;
         let mut bit = 0;
         for i in 0..self.0.len() {
@@ -81,7 +95,6 @@ impl BoxMonBitVec {
         result
     }
 
-    #[cfg(test)]
     pub fn as_u64(&self) -> u64 {
         let mut result = 0;
         for i in 0..self.0.len() {
@@ -91,6 +104,16 @@ impl BoxMonBitVec {
         }
         result
     }
+
+    pub fn as_u128(&self) -> u128 {
+        let mut result = 0;
+        for i in 0..self.0.len() {
+            if self.0[i] {
+                result |= 1 << i;
+            }
+        }
+        result
+    }
 }
 
 pub fn count_to_bits(n: usize) -> usize {
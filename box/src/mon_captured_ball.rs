@@ -1,12 +1,10 @@
 use serde::{Deserialize, Serialize};
-use strum::{EnumCount, EnumIter};
+use strum::{EnumCount, EnumIter, IntoEnumIterator};
 use strum_macros::FromRepr;
 
-use crate::mon_field::FromRepresentation;
+use crate::mon_field::{FromRepresentation, FromStringInput};
 
-#[derive(
-    FromRepr, Debug, Clone, Copy, EnumCount, EnumIter, PartialEq, Eq, Serialize, Deserialize,
-)]
+#[derive(FromRepr, Debug, Clone, Copy, EnumCount, EnumIter, PartialEq, Eq)]
 #[repr(u8)]
 pub enum BoxMonCapturedBall {
     ItemDiveBall,
@@ -29,6 +27,58 @@ impl FromRepresentation for BoxMonCapturedBall {
     }
 }
 
+impl std::fmt::Display for BoxMonCapturedBall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BoxMonCapturedBall::ItemDiveBall => "Dive Ball",
+            BoxMonCapturedBall::ItemGreatBall => "Great Ball",
+            BoxMonCapturedBall::ItemNestBall => "Nest Ball",
+            BoxMonCapturedBall::ItemNetBall => "Net Ball",
+            BoxMonCapturedBall::ItemPokeBall => "Poké Ball",
+            BoxMonCapturedBall::ItemRepeatBall => "Repeat Ball",
+            BoxMonCapturedBall::ItemTimerBall => "Timer Ball",
+            BoxMonCapturedBall::ItemUltraBall => "Ultra Ball",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl Serialize for BoxMonCapturedBall {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoxMonCapturedBall {
+    fn deserialize<D>(deserializer: D) -> Result<BoxMonCapturedBall, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        match BoxMonCapturedBall::iter().find(|ball| ball.to_string() == s) {
+            Some(ball) => Ok(ball),
+            None => Err(serde::de::Error::custom(format!(
+                "Invalid BoxMonCapturedBall: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStringInput for BoxMonCapturedBall {
+    // Accepts the summary screen name with or without spaces
+    fn try_from_string(input: &str) -> Option<Self> {
+        let normalise = |name: &str| name.replace(' ', "").replace('é', "e").to_uppercase();
+        let input = normalise(input);
+        BoxMonCapturedBall::iter().find(|ball| normalise(&ball.to_string()) == input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -48,4 +98,23 @@ mod tests {
             assert_eq!(i, decoded.as_u8());
         }
     }
+
+    #[test]
+    fn test_from_string() {
+        for ball in BoxMonCapturedBall::iter() {
+            assert_eq!(
+                BoxMonCapturedBall::try_from_string(&ball.to_string()),
+                Some(ball)
+            );
+        }
+        assert_eq!(
+            BoxMonCapturedBall::try_from_string("POKEBALL"),
+            Some(BoxMonCapturedBall::ItemPokeBall)
+        );
+        assert_eq!(
+            BoxMonCapturedBall::try_from_string("DIVE BALL"),
+            Some(BoxMonCapturedBall::ItemDiveBall)
+        );
+        assert_eq!(BoxMonCapturedBall::try_from_string("MASTER BALL"), None);
+    }
 }
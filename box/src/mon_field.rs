@@ -51,6 +51,20 @@ where
     }
 }
 
+// Number of valid values a field can take, used when packing fields as mixed radix digits
+pub trait Radix {
+    fn radix() -> u128;
+}
+
+impl<T> Radix for T
+where
+    T: IntoEnumIterator,
+{
+    fn radix() -> u128 {
+        T::iter().count() as u128
+    }
+}
+
 #[cfg(test)]
 pub trait PossibleValues {
     fn possible_values() -> usize;
@@ -83,10 +97,97 @@ where
 
     fn game_value_to_bits(&self) -> Result<BoxMonBitVec, FromGameValueError> {
         let value = self.to_u8();
+        // Variants past the last power of two don't fit in the bits
+        if (value as usize) >> Self::bit_count() != 0 {
+            return Err(FromGameValueError::UnsupportedValue);
+        }
         Ok(BoxMonBitVec::new(Self::bit_count(), value))
     }
 }
 
+// Numbers too large for a u128 are little endian bytes. Radixes must stay under 2^100 so
+// a byte times the radix can't overflow.
+fn mul_add(number: &mut Vec<u8>, mul: u128, add: u128) {
+    let mut carry = add;
+    for byte in number.iter_mut() {
+        let value = *byte as u128 * mul + carry;
+        *byte = value as u8;
+        carry = value >> 8;
+    }
+    while carry != 0 {
+        number.push(carry as u8);
+        carry >>= 8;
+    }
+}
+
+fn div_rem(number: &mut [u8], div: u128) -> u128 {
+    let mut rem = 0;
+    for byte in number.iter_mut().rev() {
+        let value = (rem << 8) | *byte as u128;
+        *byte = (value / div) as u8;
+        rem = value % div;
+    }
+    rem
+}
+
+fn bit_length(number: &[u8]) -> usize {
+    match number.iter().rposition(|byte| *byte != 0) {
+        Some(top) => top * 8 + (8 - number[top].leading_zeros() as usize),
+        None => 0,
+    }
+}
+
+// Whole bits that fit in 0 to max_digits digits of the radix, floor(log2(radix^n))
+pub fn mixed_radix_bit_counts(radix: u128, max_digits: usize) -> Vec<usize> {
+    let mut number = vec![1];
+    let mut counts = vec![0];
+    for _ in 0..max_digits {
+        mul_add(&mut number, radix, 0);
+        counts.push(bit_length(&number) - 1);
+    }
+    counts
+}
+
+// Reads the bits as a little endian number and splits it into digits, least significant first
+pub fn bits_to_digits(
+    bits: &BoxMonBitVec,
+    radix: u128,
+    digits: usize,
+) -> Result<Vec<u128>, FromGameValueError> {
+    if bits.0.len() > mixed_radix_bit_counts(radix, digits)[digits] {
+        return Err(FromGameValueError::BadSize(bits.0.len()));
+    }
+
+    let mut number = bits.to_raw();
+    Ok((0..digits).map(|_| div_rem(&mut number, radix)).collect())
+}
+
+pub fn digits_to_bits(
+    digits: &[u128],
+    radix: u128,
+    bit_count: usize,
+) -> Result<BoxMonBitVec, ToGameValueError> {
+    let mut number = vec![];
+    for digit in digits.iter().rev() {
+        if *digit >= radix {
+            return Err(ToGameValueError::BitsValueOutOfRange);
+        }
+        mul_add(&mut number, radix, *digit);
+    }
+
+    // Misread digits can give a number no data would have produced
+    if bit_length(&number) > bit_count {
+        return Err(ToGameValueError::BitsValueOutOfRange);
+    }
+
+    let mut bits = BoxMonBitVec::new_from_raw(&number);
+    bits.0.truncate(bit_count);
+    while bits.0.len() < bit_count {
+        bits.0.push(false);
+    }
+    Ok(bits)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ToGameValueError {
     BadBitsLength,
@@ -96,4 +197,30 @@ pub enum ToGameValueError {
 #[derive(Debug, Clone, Copy)]
 pub enum FromGameValueError {
     BadSize(usize),
+    UnsupportedValue,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixed_radix_to_and_from() {
+        assert_eq!(mixed_radix_bit_counts(3, 4), vec![0, 1, 3, 4, 6]);
+        assert_eq!(mixed_radix_bit_counts(256, 2), vec![0, 8, 16]);
+
+        let radix = 33 * (1 << 63);
+        let bits = BoxMonBitVec::new_from_raw(
+            &(0..25)
+                .map(|i: u32| (i * 37 + 5) as u8)
+                .collect::<Vec<u8>>(),
+        );
+        let digits = bits_to_digits(&bits, radix, 3).unwrap();
+        assert!(digits.iter().all(|digit| *digit < radix));
+        assert_eq!(digits_to_bits(&digits, radix, bits.0.len()).unwrap(), bits);
+
+        assert!(bits_to_digits(&bits, radix, 2).is_err());
+        assert!(digits_to_bits(&[radix], radix, 68).is_err());
+        assert!(digits_to_bits(&[radix - 1], radix, 68).is_err());
+    }
 }
@@ -44,43 +44,45 @@ pub enum BoxMonHeldItem {
     XSpeed,
 }
 
-impl ToString for BoxMonHeldItem {
-    fn to_string(&self) -> String {
-        match self {
-            BoxMonHeldItem::Empty => "".to_string(),
-            BoxMonHeldItem::Antidote => "Antidote".to_string(),
-            BoxMonHeldItem::Awakening => "Awakening".to_string(),
-            BoxMonHeldItem::BurnHeal => "Burn Heal".to_string(),
-            BoxMonHeldItem::DireHit => "Dire Hit".to_string(),
-            BoxMonHeldItem::EnergyPower => "Energypowder".to_string(),
-            BoxMonHeldItem::EnergyRoot => "Energy Root".to_string(),
-            BoxMonHeldItem::EscapeRope => "Escape Rope".to_string(),
-            BoxMonHeldItem::FluffyTail => "Fluffy Tail".to_string(),
-            BoxMonHeldItem::FullHeal => "Full Heal".to_string(),
-            BoxMonHeldItem::FullRestore => "Full Restore".to_string(),
-            BoxMonHeldItem::GreatBall => "Great Ball".to_string(),
-            BoxMonHeldItem::GuardSpec => "Guard Spec.".to_string(),
-            BoxMonHeldItem::HealPowder => "Heal Powder".to_string(),
-            BoxMonHeldItem::HyperPotion => "Hyper Potion".to_string(),
-            BoxMonHeldItem::IceHeal => "Ice Heal".to_string(),
-            BoxMonHeldItem::MaxPotion => "Max Potion".to_string(),
-            BoxMonHeldItem::MaxRepel => "Max Repel".to_string(),
-            BoxMonHeldItem::ParlyzHeal => "Parlyz Heal".to_string(),
-            BoxMonHeldItem::PokeBall => "Poké Ball".to_string(),
-            BoxMonHeldItem::Potion => "Potion".to_string(),
-            BoxMonHeldItem::NestBall => "Nest Ball".to_string(),
-            BoxMonHeldItem::Repel => "Repel".to_string(),
-            BoxMonHeldItem::RevivalHerb => "Revival Herb".to_string(),
-            BoxMonHeldItem::Revive => "Revive".to_string(),
-            BoxMonHeldItem::SuperPotion => "Super Potion".to_string(),
-            BoxMonHeldItem::SuperRepel => "Super Repel".to_string(),
-            BoxMonHeldItem::TimerBall => "Timer Ball".to_string(),
-            BoxMonHeldItem::XAccuracy => "X Accuracy".to_string(),
-            BoxMonHeldItem::XAttack => "X Attack".to_string(),
-            BoxMonHeldItem::XDefend => "X Defend".to_string(),
-            BoxMonHeldItem::XSpecial => "X Special".to_string(),
-            BoxMonHeldItem::XSpeed => "X Speed".to_string(),
-        }
+impl std::fmt::Display for BoxMonHeldItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BoxMonHeldItem::Empty => "",
+            BoxMonHeldItem::Antidote => "Antidote",
+            BoxMonHeldItem::Awakening => "Awakening",
+            BoxMonHeldItem::BurnHeal => "Burn Heal",
+            BoxMonHeldItem::DireHit => "Dire Hit",
+            BoxMonHeldItem::EnergyPower => "Energypowder",
+            BoxMonHeldItem::EnergyRoot => "Energy Root",
+            BoxMonHeldItem::EscapeRope => "Escape Rope",
+            BoxMonHeldItem::FluffyTail => "Fluffy Tail",
+            BoxMonHeldItem::FullHeal => "Full Heal",
+            BoxMonHeldItem::FullRestore => "Full Restore",
+            BoxMonHeldItem::GreatBall => "Great Ball",
+            BoxMonHeldItem::GuardSpec => "Guard Spec.",
+            BoxMonHeldItem::HealPowder => "Heal Powder",
+            BoxMonHeldItem::HyperPotion => "Hyper Potion",
+            BoxMonHeldItem::IceHeal => "Ice Heal",
+            BoxMonHeldItem::MaxPotion => "Max Potion",
+            BoxMonHeldItem::MaxRepel => "Max Repel",
+            BoxMonHeldItem::ParlyzHeal => "Parlyz Heal",
+            BoxMonHeldItem::PokeBall => "Poké Ball",
+            BoxMonHeldItem::Potion => "Potion",
+            BoxMonHeldItem::NestBall => "Nest Ball",
+            BoxMonHeldItem::Repel => "Repel",
+            BoxMonHeldItem::RevivalHerb => "Revival Herb",
+            BoxMonHeldItem::Revive => "Revive",
+            BoxMonHeldItem::SuperPotion => "Super Potion",
+            BoxMonHeldItem::SuperRepel => "Super Repel",
+            BoxMonHeldItem::TimerBall => "Timer Ball",
+            BoxMonHeldItem::XAccuracy => "X Accuracy",
+            BoxMonHeldItem::XAttack => "X Attack",
+            BoxMonHeldItem::XDefend => "X Defend",
+            BoxMonHeldItem::XSpecial => "X Special",
+            BoxMonHeldItem::XSpeed => "X Speed",
+        };
+
+        f.write_str(name)
     }
 }
 
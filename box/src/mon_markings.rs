@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::mon_field::{BitCount, FromRepresentation, FromStringInput, Radix};
+
+// The symbols set from the PC menu, each one is independent so every combination is valid
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BoxMonMarkings {
+    pub circle: bool,
+    pub square: bool,
+    pub triangle: bool,
+    pub heart: bool,
+}
+
+// In the order the game stores them, lowest bit first
+const SYMBOLS: [char; 4] = ['●', '■', '▲', '♥'];
+
+impl BoxMonMarkings {
+    fn flags(&self) -> [bool; 4] {
+        [self.circle, self.square, self.triangle, self.heart]
+    }
+}
+
+impl BitCount for BoxMonMarkings {
+    fn bit_count() -> usize {
+        SYMBOLS.len()
+    }
+}
+
+impl Radix for BoxMonMarkings {
+    fn radix() -> u128 {
+        1 << SYMBOLS.len()
+    }
+}
+
+impl FromRepresentation for BoxMonMarkings {
+    fn from_repr(repr: u8) -> Option<Self> {
+        if repr >> SYMBOLS.len() != 0 {
+            return None;
+        }
+
+        Some(BoxMonMarkings {
+            circle: repr & 1 != 0,
+            square: repr & 2 != 0,
+            triangle: repr & 4 != 0,
+            heart: repr & 8 != 0,
+        })
+    }
+
+    fn to_u8(&self) -> u8 {
+        self.flags()
+            .iter()
+            .enumerate()
+            .map(|(i, set)| (*set as u8) << i)
+            .sum()
+    }
+}
+
+impl std::fmt::Display for BoxMonMarkings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (symbol, set) in SYMBOLS.iter().zip(self.flags()) {
+            if set {
+                write!(f, "{}", symbol)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for BoxMonMarkings {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoxMonMarkings {
+    fn deserialize<D>(deserializer: D) -> Result<BoxMonMarkings, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        match BoxMonMarkings::try_from_string(&s) {
+            Some(markings) => Ok(markings),
+            None => Err(serde::de::Error::custom(format!(
+                "Invalid BoxMonMarkings: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStringInput for BoxMonMarkings {
+    // The set symbols in any order, an empty string is a mon without markings
+    fn try_from_string(input: &str) -> Option<Self> {
+        let mut repr = 0;
+        for c in input.chars() {
+            let bit = 1 << SYMBOLS.iter().position(|symbol| *symbol == c)?;
+            if repr & bit != 0 {
+                return None;
+            }
+            repr |= bit;
+        }
+
+        Self::from_repr(repr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mon_field::GameSerializer, BoxMonBitVec};
+
+    use super::*;
+
+    #[test]
+    fn test_to_and_from() {
+        for i in 0..BoxMonMarkings::radix() as u8 {
+            let starting = BoxMonBitVec::new(BoxMonMarkings::bit_count(), i);
+            let markings = BoxMonMarkings::bits_to_game_value(&starting).unwrap();
+            assert_eq!(markings.game_value_to_bits().unwrap(), starting);
+            assert_eq!(
+                BoxMonMarkings::try_from_string(&markings.to_string()),
+                Some(markings)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_string() {
+        assert_eq!(
+            BoxMonMarkings::try_from_string("♥●"),
+            Some(BoxMonMarkings {
+                circle: true,
+                heart: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            BoxMonMarkings::try_from_string(""),
+            Some(BoxMonMarkings::default())
+        );
+        assert_eq!(BoxMonMarkings::try_from_string("●●"), None);
+        assert_eq!(BoxMonMarkings::try_from_string("★"), None);
+    }
+}
@@ -1,6 +1,5 @@
-use bit_vec::BitVec;
 use serde::{Deserialize, Serialize};
-use strum::{EnumCount, EnumIter};
+use strum::{EnumCount, EnumIter, IntoEnumIterator};
 use strum_macros::FromRepr;
 
 use crate::{
@@ -80,6 +79,18 @@ pub enum BoxMonCharacter {
     Ellipsis,
     Male,
     Female,
+    // Only reachable through the full alphabets
+    UpperL,
+    LowerL,
+    Zero,
+    One,
+    Space,
+    Period,
+    Comma,
+    LeftDoubleQuote,
+    RightDoubleQuote,
+    LeftSingleQuote,
+    RightSingleQuote,
 }
 
 impl FromStringInput for BoxMonCharacter {
@@ -150,6 +161,17 @@ impl FromStringInput for BoxMonCharacter {
             "…" => BoxMonCharacter::Ellipsis,
             "♂" => BoxMonCharacter::Male,
             "♀" => BoxMonCharacter::Female,
+            "L" => BoxMonCharacter::UpperL,
+            "l" => BoxMonCharacter::LowerL,
+            "0" => BoxMonCharacter::Zero,
+            "1" => BoxMonCharacter::One,
+            " " => BoxMonCharacter::Space,
+            "." => BoxMonCharacter::Period,
+            "," => BoxMonCharacter::Comma,
+            "“" => BoxMonCharacter::LeftDoubleQuote,
+            "”" => BoxMonCharacter::RightDoubleQuote,
+            "‘" => BoxMonCharacter::LeftSingleQuote,
+            "’" => BoxMonCharacter::RightSingleQuote,
             _ => return None,
         };
 
@@ -157,9 +179,9 @@ impl FromStringInput for BoxMonCharacter {
     }
 }
 
-impl ToString for BoxMonCharacter {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for BoxMonCharacter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let character = match self {
             BoxMonCharacter::LowerA => "a",
             BoxMonCharacter::UpperA => "A",
             BoxMonCharacter::LowerB => "b",
@@ -225,8 +247,20 @@ impl ToString for BoxMonCharacter {
             BoxMonCharacter::Ellipsis => "…",
             BoxMonCharacter::Male => "♂",
             BoxMonCharacter::Female => "♀",
-        }
-        .to_string()
+            BoxMonCharacter::UpperL => "L",
+            BoxMonCharacter::LowerL => "l",
+            BoxMonCharacter::Zero => "0",
+            BoxMonCharacter::One => "1",
+            BoxMonCharacter::Space => " ",
+            BoxMonCharacter::Period => ".",
+            BoxMonCharacter::Comma => ",",
+            BoxMonCharacter::LeftDoubleQuote => "“",
+            BoxMonCharacter::RightDoubleQuote => "”",
+            BoxMonCharacter::LeftSingleQuote => "‘",
+            BoxMonCharacter::RightSingleQuote => "’",
+        };
+
+        f.write_str(character)
     }
 }
 
@@ -240,6 +274,133 @@ impl FromRepresentation for BoxMonCharacter {
     }
 }
 
+// Characters easily confused with I and O when reading a screenshot, spaces are only
+// visible as gaps
+const AMBIGUOUS_CHARACTERS: [BoxMonCharacter; 4] = [
+    BoxMonCharacter::LowerL,
+    BoxMonCharacter::Zero,
+    BoxMonCharacter::One,
+    BoxMonCharacter::Space,
+];
+
+const LEGACY_ALPHABET_SIZE: usize = 64;
+const NAME_LENGTH: u32 = 10;
+
+#[derive(
+    FromRepr,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    EnumCount,
+    EnumIter,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[repr(u8)]
+pub enum NameAlphabet {
+    // The original 64 characters, 6 bits each
+    #[default]
+    Legacy,
+    // Everything on the Emerald naming keyboard
+    Full,
+    // Full without the characters that are easy to misread
+    FullUnambiguous,
+}
+
+impl NameAlphabet {
+    pub fn characters(&self) -> Vec<BoxMonCharacter> {
+        let all = BoxMonCharacter::iter();
+        match self {
+            NameAlphabet::Legacy => all.take(LEGACY_ALPHABET_SIZE).collect(),
+            NameAlphabet::Full => all.collect(),
+            NameAlphabet::FullUnambiguous => {
+                all.filter(|c| !AMBIGUOUS_CHARACTERS.contains(c)).collect()
+            }
+        }
+    }
+
+    pub fn contains(&self, character: BoxMonCharacter) -> bool {
+        self.characters().contains(&character)
+    }
+
+    pub fn name_combinations(&self) -> u128 {
+        (self.characters().len() as u128).pow(NAME_LENGTH)
+    }
+
+    // Names are one base N number so characters don't each round down to a power of two
+    pub fn name_bit_count(&self) -> usize {
+        (u128::BITS - 1 - self.name_combinations().leading_zeros()) as usize
+    }
+
+    pub fn name_to_value(&self, name: &BoxMonName) -> Result<u128, FromGameValueError> {
+        let characters = self.characters();
+        let base = characters.len() as u128;
+
+        let mut value: u128 = 0;
+        for character in name.name.iter().rev() {
+            let digit = match characters.iter().position(|c| c == character) {
+                Some(digit) => digit as u128,
+                None => return Err(FromGameValueError::UnsupportedValue),
+            };
+            value = value * base + digit;
+        }
+
+        Ok(value)
+    }
+
+    pub fn value_to_name(&self, mut value: u128) -> Result<BoxMonName, ToGameValueError> {
+        if value >= self.name_combinations() {
+            return Err(ToGameValueError::BitsValueOutOfRange);
+        }
+
+        let characters = self.characters();
+        let base = characters.len() as u128;
+
+        let mut name = [BoxMonCharacter::LowerA; 10];
+        for character in name.iter_mut() {
+            *character = characters[(value % base) as usize];
+            value /= base;
+        }
+
+        Ok(BoxMonName { name })
+    }
+
+    pub fn name_to_bits(&self, name: &BoxMonName) -> Result<BoxMonBitVec, FromGameValueError> {
+        let value = self.name_to_value(name)?;
+
+        // Names past the largest bit pattern can't be produced when encoding
+        if value >> self.name_bit_count() != 0 {
+            return Err(FromGameValueError::UnsupportedValue);
+        }
+
+        Ok(BoxMonBitVec::new(self.name_bit_count(), value as u64))
+    }
+
+    pub fn bits_to_name(&self, value: &BoxMonBitVec) -> Result<BoxMonName, ToGameValueError> {
+        if value.0.len() != self.name_bit_count() {
+            return Err(ToGameValueError::BadBitsLength);
+        }
+
+        self.value_to_name(value.as_u64() as u128)
+    }
+}
+
+impl std::str::FromStr for NameAlphabet {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "legacy" => Ok(NameAlphabet::Legacy),
+            "full" => Ok(NameAlphabet::Full),
+            "full-unambiguous" => Ok(NameAlphabet::FullUnambiguous),
+            _ => Err(format!("Unknown alphabet {}", input)),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct BoxMonName {
     name: [BoxMonCharacter; 10],
@@ -249,6 +410,10 @@ impl BoxMonName {
     pub fn new(name: [BoxMonCharacter; 10]) -> Self {
         BoxMonName { name }
     }
+
+    pub fn characters(&self) -> &[BoxMonCharacter; 10] {
+        &self.name
+    }
 }
 
 impl FromStringInput for BoxMonName {
@@ -259,20 +424,20 @@ impl FromStringInput for BoxMonName {
 
         let mut name = [BoxMonCharacter::LowerA; 10];
         for (i, character) in input.chars().enumerate() {
-            let character = match BoxMonCharacter::try_from_string(&character.to_string()) {
-                Some(character) => character,
-                None => return None,
-            };
-            name[i] = character;
+            name[i] = BoxMonCharacter::try_from_string(&character.to_string())?;
         }
 
         Some(BoxMonName { name })
     }
 }
 
-impl ToString for BoxMonName {
-    fn to_string(&self) -> String {
-        self.name.iter().map(|c| c.to_string()).collect()
+impl std::fmt::Display for BoxMonName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for character in &self.name {
+            write!(f, "{}", character)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -300,7 +465,7 @@ impl<'de> Deserialize<'de> for BoxMonName {
 
 impl BitCount for BoxMonName {
     fn bit_count() -> usize {
-        BoxMonCharacter::bit_count() * 10
+        NameAlphabet::Legacy.name_bit_count()
     }
 }
 
@@ -309,40 +474,14 @@ impl GameSerializer for BoxMonName {
     where
         Self: Sized,
     {
-        if value.0.len() != Self::bit_count() {
-            return Err(ToGameValueError::BadBitsLength);
-        }
-
-        let mut name = [BoxMonCharacter::LowerA; 10];
-        for i in 0..10 {
-            let start = i * BoxMonCharacter::bit_count();
-            let end = (i + 1) * BoxMonCharacter::bit_count();
-            let character_bits = value.chunk(start, end);
-            let character = match BoxMonCharacter::bits_to_game_value(&character_bits) {
-                Ok(character) => character,
-                Err(err) => return Err(err),
-            };
-            name[i] = character;
-        }
-
-        Ok(BoxMonName { name })
+        NameAlphabet::Legacy.bits_to_name(value)
     }
 
     fn game_value_to_bits(&self) -> Result<BoxMonBitVec, FromGameValueError>
     where
         Self: Sized,
     {
-        let mut bits = BitVec::new();
-        for i in 0..10 {
-            let character = self.name[i];
-            let character_bits = match character.game_value_to_bits() {
-                Ok(bits) => bits,
-                Err(err) => return Err(err),
-            };
-            bits.extend(character_bits.0.iter());
-        }
-
-        Ok(BoxMonBitVec(bits))
+        NameAlphabet::Legacy.name_to_bits(self)
     }
 }
 
@@ -361,6 +500,7 @@ mod tests {
     #[test]
     fn test_char_to_and_from() {
         assert_eq!(BoxMonCharacter::possible_values(), 64);
+        assert!(BoxMonCharacter::Female.game_value_to_bits().is_err());
 
         for i in 0..BoxMonCharacter::possible_values() as u8 {
             let starting = BoxMonBitVec::new(BoxMonCharacter::bit_count(), i);
@@ -449,4 +589,51 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_alphabet_sizes() {
+        assert_eq!(NameAlphabet::Legacy.characters().len(), 64);
+        assert_eq!(NameAlphabet::Full.characters().len(), 76);
+        assert_eq!(NameAlphabet::FullUnambiguous.characters().len(), 72);
+
+        assert_eq!(NameAlphabet::Legacy.name_bit_count(), 60);
+        assert_eq!(NameAlphabet::Full.name_bit_count(), 62);
+        assert_eq!(NameAlphabet::FullUnambiguous.name_bit_count(), 61);
+
+        assert!(!NameAlphabet::FullUnambiguous.contains(BoxMonCharacter::LowerL));
+        assert!(NameAlphabet::FullUnambiguous.contains(BoxMonCharacter::UpperL));
+    }
+
+    #[test]
+    fn test_legacy_alphabet_matches_per_character_layout() {
+        let name = BoxMonName::try_from_string("♂Iq5/iGJD9").unwrap();
+        let bits = NameAlphabet::Legacy.name_to_bits(&name).unwrap();
+
+        for (i, character) in name.name.iter().enumerate() {
+            assert_eq!(
+                bits.chunk(i * 6, (i + 1) * 6),
+                character.game_value_to_bits().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_alphabet_to_and_from() {
+        let mut rng = ChaCha8Rng::seed_from_u64(6);
+        for alphabet in NameAlphabet::iter() {
+            for _ in 0..1000 {
+                let value = rng.gen::<u64>() % (1 << alphabet.name_bit_count());
+                let starting = BoxMonBitVec::new(alphabet.name_bit_count(), value);
+                let name = alphabet.bits_to_name(&starting).unwrap();
+                assert_eq!(alphabet.name_to_bits(&name).unwrap(), starting);
+            }
+        }
+
+        let name = BoxMonName::try_from_string("Lo1, “0l”A").unwrap();
+        assert_eq!(name.to_string(), "Lo1, “0l”A");
+        assert!(NameAlphabet::Legacy.name_to_bits(&name).is_err());
+        assert!(NameAlphabet::FullUnambiguous.name_to_bits(&name).is_err());
+        let bits = NameAlphabet::Full.name_to_bits(&name).unwrap();
+        assert_eq!(NameAlphabet::Full.bits_to_name(&bits).unwrap(), name);
+    }
 }
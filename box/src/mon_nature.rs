@@ -0,0 +1,88 @@
+use serde_derive::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumCount, EnumIter, FromRepr};
+
+use crate::mon_field::{FromRepresentation, FromStringInput};
+
+// In game order, a mon's nature is its personality value mod 25. Set while catching with a
+// Synchronize lead or by catching until the right one turns up.
+#[derive(
+    FromRepr, Debug, Clone, Copy, EnumCount, EnumIter, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[repr(u8)]
+pub enum BoxMonNature {
+    HARDY,
+    LONELY,
+    BRAVE,
+    ADAMANT,
+    NAUGHTY,
+    BOLD,
+    DOCILE,
+    RELAXED,
+    IMPISH,
+    LAX,
+    TIMID,
+    HASTY,
+    SERIOUS,
+    JOLLY,
+    NAIVE,
+    MODEST,
+    MILD,
+    QUIET,
+    BASHFUL,
+    RASH,
+    CALM,
+    GENTLE,
+    SASSY,
+    CAREFUL,
+    QUIRKY,
+}
+
+impl FromRepresentation for BoxMonNature {
+    fn from_repr(repr: u8) -> Option<Self> {
+        Self::from_repr(repr)
+    }
+
+    fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl FromStringInput for BoxMonNature {
+    fn try_from_string(input: &str) -> Option<Self> {
+        Self::iter().find(|nature| format!("{:?}", nature) == input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mon_field::{BitCount, GameSerializer, Radix},
+        BoxMonBitVec,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_to_and_from() {
+        assert_eq!(BoxMonNature::radix(), 25);
+        assert_eq!(BoxMonNature::bit_count(), 4);
+        for i in 0..1 << BoxMonNature::bit_count() {
+            let starting = BoxMonBitVec::new(BoxMonNature::bit_count(), i as u8);
+            let nature = BoxMonNature::bits_to_game_value(&starting).unwrap();
+            assert_eq!(nature.game_value_to_bits().unwrap(), starting);
+        }
+
+        // Natures past the last power of two only fit with mixed radix packing
+        assert!(BoxMonNature::QUIRKY.game_value_to_bits().is_err());
+    }
+
+    #[test]
+    fn test_from_string() {
+        assert_eq!(
+            BoxMonNature::try_from_string("ADAMANT"),
+            Some(BoxMonNature::ADAMANT)
+        );
+        assert_eq!(BoxMonNature::try_from_string("adamant"), None);
+    }
+}
@@ -1,26 +1,140 @@
-use crate::box_mon::BoxMon;
-use crate::mon_field::{BitCount, ByteCount, GameSerializer};
+use std::io::Read;
+
+use crate::box_mon::{BoxMon, MonLayout, MonPacking};
+use crate::error::{GameValueError, MonFsError};
+use crate::fec::{self, FEC_HEADER_SIZE};
+use crate::file_pc::parity_mons_for_bytes;
+use crate::mon_field::{
+    bits_to_digits, digits_to_bits, mixed_radix_bit_counts, BitCount, GameSerializer,
+};
+use crate::mon_name::NameAlphabet;
+use crate::pc_box::PcBox;
+use crate::profile::{profile_by_id, EncodingProfile, CUSTOM_PROFILE_ID, FORMAT_VERSION};
 use crate::BoxMonBitVec;
 use bit_vec::BitVec;
 use serde_derive::{Deserialize, Serialize};
 
-const PC_BOX_SIZE: usize = 30;
-const NUM_PC_BOXES: usize = 14;
+pub const PC_BOX_SIZE: usize = 30;
+pub const NUM_PC_BOXES: usize = 14;
 pub const NUM_OF_MONS: usize = PC_BOX_SIZE * NUM_PC_BOXES;
-const NUM_OF_DATA_MONS: usize = NUM_OF_MONS - 2;
+pub(crate) const NUM_OF_DATA_MONS: usize = NUM_OF_MONS - 2;
+
+// Layout of the header mon in slot 0 (offset, size) in bits
+const HEADER_PADDING: (usize, usize) = (0, 8);
+const HEADER_FLAGS: (usize, usize) = (8, 8);
+const HEADER_ALPHABET: (usize, usize) = (16, 2);
+// Mons in each mixed radix run, 0 when every field is packed as bits
+const HEADER_RUN_LENGTH: (usize, usize) = (18, 9);
+const HEADER_CAPTURED_BALL: (usize, usize) = (27, 1);
+pub(crate) const HEADER_VERSION: (usize, usize) = (28, 4);
+const HEADER_PROFILE: (usize, usize) = (32, 8);
+const HEADER_MARKINGS: (usize, usize) = (40, 1);
+const HEADER_NATURE: (usize, usize) = (41, 1);
+// Bytes of the data stream held by the box titles and wallpapers
+const HEADER_BOX_BYTES: (usize, usize) = (42, 8);
+// Bytes of the data stream held by the party and item PC after the PC is full
+const HEADER_STORAGE_BYTES: (usize, usize) = (50, 8);
+// Mons after the header holding the permutation table, 0 when data mons are in slot order
+const HEADER_PERMUTATION_MONS: (usize, usize) = (58, 6);
+// Bits 64 to 67 are free
+
+pub const PC_FLAG_FEC: u8 = 0x01;
+pub const PC_FLAG_CHECKSUM: u8 = 0x02;
+// The data stream starts in the box titles and wallpapers before moving on to the mons
+pub const PC_FLAG_BOX_DATA: u8 = 0x04;
+// The data stream carries on into the party and item PC, see Storage
+pub const PC_FLAG_STORAGE: u8 = 0x08;
+// The PC holds one part of a FilePc spread over several PCs, see PcSet
+pub const PC_FLAG_SET: u8 = 0x10;
+// Files are stored one after another instead of as one blob so adding a file only appends
+pub const PC_FLAG_LOG: u8 = 0x20;
+// All the files are compressed together as one stream, the codec ID is its first byte. See
+// FilePc::serialize_with_options
+pub const PC_FLAG_ARCHIVE: u8 = 0x40;
+// The data is encrypted with a password, see crypto. This is the last bit of the flags byte,
+// any new flag has to go in the free header bits
+pub const PC_FLAG_ENCRYPTED: u8 = 0x80;
+
+// Bits for each entry of the permutation table, enough to index every data mon
+const PERMUTATION_INDEX_BITS: usize = 9;
+pub(crate) const MAX_PERMUTATION_MONS: usize = (1 << HEADER_PERMUTATION_MONS.1) - 1;
+
+// Bits at the end of each data mon used for a CRC when PC_FLAG_CHECKSUM is set
+const CHECKSUM_BITS: usize = 4;
+const CHECKSUM_POLY: u8 = 0x3;
+
+// Data mons data_mon.. moved to the slots slot.., counting from the first slot after the
+// permutation table
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlotRun {
+    pub data_mon: usize,
+    pub slot: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MonPosition {
+    pub box_index: usize,
+    pub slot: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub checked_mons: usize,
+    pub suspect: Vec<MonPosition>,
+}
+
+// What has to change in game to turn one PC into another
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PcDiff {
+    pub mons: Vec<MonPosition>,
+    pub boxes: Vec<usize>,
+}
+
+impl PcDiff {
+    pub fn is_empty(&self) -> bool {
+        self.mons.is_empty() && self.boxes.is_empty()
+    }
+}
+
+// How much of the PC is taken up, for listing a PC without decoding it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PcUsage {
+    pub version: u8,
+    pub flags: u8,
+    pub profile: Option<String>,
+    pub used_mons: usize,
+    pub data_mons: usize,
+    pub permutation_mons: usize,
+    // Unused bits at the end of the last data mon
+    pub padding_bits: usize,
+    pub box_bytes_used: usize,
+    pub remaining_bytes: usize,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PC {
     pub mons: Vec<Option<BoxMon>>,
+    // Empty unless PC_FLAG_BOX_DATA is set and data has been written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boxes: Vec<PcBox>,
     #[serde(skip)]
     current_read_offset: usize,
     #[serde(skip)]
     raw_cache: Option<BoxMonBitVec>,
 }
 
+// What the data mons of PC::new() hold with the default layout, other layouts report their
+// own capacity through remaining_bytes
 impl BitCount for PC {
     fn bit_count() -> usize {
-        return (BoxMon::byte_count() * NUM_OF_DATA_MONS) * 8;
+        BoxMon::bit_count() * NUM_OF_DATA_MONS
+    }
+}
+
+impl Default for PC {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -28,6 +142,7 @@ impl PC {
     pub fn new() -> PC {
         PC {
             mons: vec![None; NUM_OF_MONS],
+            boxes: vec![],
             current_read_offset: 0,
             raw_cache: None,
         }
@@ -39,30 +154,362 @@ impl PC {
         }
     }
 
-    pub fn set_padding_amount(&mut self, padding_amount: u8) {
-        let mut bits = BitVec::from_elem(8, false);
+    fn get_header_bits(&self) -> Result<BoxMonBitVec, MonFsError> {
+        match self.mons[0] {
+            Some(mon) => match mon.game_value_to_bits() {
+                Ok(bits) => Ok(bits),
+                Err(_) => Err(MonFsError::InvalidPaddingMon),
+            },
+            None => Ok(BoxMonBitVec(BitVec::from_elem(BoxMon::bit_count(), false))),
+        }
+    }
 
-        for i in 0..8 {
-            bits.set(i, padding_amount & (1 << i) != 0);
+    fn get_header_field(&self, field: (usize, usize)) -> Result<u64, MonFsError> {
+        let bits = self.get_header_bits()?;
+        let mut value = 0;
+        for i in 0..field.1 {
+            if bits.0[field.0 + i] {
+                value |= 1 << i;
+            }
         }
+        Ok(value)
+    }
 
-        while bits.len() < BoxMon::bit_count() {
-            bits.push(false);
+    pub(crate) fn set_header_field(
+        &mut self,
+        field: (usize, usize),
+        value: u64,
+    ) -> Result<(), MonFsError> {
+        let mut bits = self.get_header_bits()?;
+        for i in 0..field.1 {
+            bits.0.set(field.0 + i, value & (1 << i) != 0);
         }
 
-        self.mons[0] = Some(BoxMon::bits_to_game_value(&BoxMonBitVec(bits)).unwrap());
+        match BoxMon::bits_to_game_value(&bits) {
+            Ok(mon) => self.mons[0] = Some(mon),
+            Err(_) => return Err(MonFsError::InvalidPaddingMon),
+        }
+        self.raw_cache = None;
+
+        Ok(())
     }
 
-    pub fn get_padding_amount(&self) -> u8 {
-        let mon = self.mons[0].as_ref().unwrap();
-        let bits = mon.game_value_to_bits().unwrap();
-        let mut padding = 0;
-        for i in 0..8 {
-            if bits.0[i] {
-                padding |= 1 << i;
+    pub fn set_padding_amount(&mut self, padding_amount: u8) -> Result<(), MonFsError> {
+        self.set_header_field(HEADER_PADDING, padding_amount as u64)
+    }
+
+    pub(crate) fn set_storage_bytes(&mut self, bytes: usize) -> Result<(), MonFsError> {
+        self.set_header_field(HEADER_STORAGE_BYTES, bytes as u64)
+    }
+
+    pub(crate) fn get_storage_bytes(&self) -> Result<usize, MonFsError> {
+        if self.get_flags()? & PC_FLAG_STORAGE == 0 {
+            return Ok(0);
+        }
+        Ok(self.get_header_field(HEADER_STORAGE_BYTES)? as usize)
+    }
+
+    pub fn get_padding_amount(&self) -> Result<u8, MonFsError> {
+        let padding = self.get_header_field(HEADER_PADDING)? as usize;
+        if padding >= self.payload_bit_count()? {
+            return Err(MonFsError::InvalidPaddingMon);
+        }
+        Ok(padding as u8)
+    }
+
+    pub fn set_flags(&mut self, flags: u8) -> Result<(), MonFsError> {
+        self.set_header_field(HEADER_FLAGS, flags as u64)
+    }
+
+    pub fn get_flags(&self) -> Result<u8, MonFsError> {
+        Ok(self.get_header_field(HEADER_FLAGS)? as u8)
+    }
+
+    // Mons spent on parity when the data was written, PcEncodeOptions::parity_mons
+    pub fn parity_mons(&self) -> Result<usize, MonFsError> {
+        if self.get_flags()? & PC_FLAG_FEC == 0 {
+            return Ok(0);
+        }
+
+        let mut pc = self.clone();
+        pc.current_read_offset = 0;
+        let mut header = [0; FEC_HEADER_SIZE];
+        pc.read_exact(&mut header)?;
+        Ok(parity_mons_for_bytes(
+            &self.get_layout()?,
+            fec::parity_budget(&header)?,
+        ))
+    }
+
+    pub fn get_version(&self) -> Result<u8, MonFsError> {
+        let version = self.get_header_field(HEADER_VERSION)? as u8;
+        if version > FORMAT_VERSION {
+            return Err(MonFsError::UnsupportedVersion(version));
+        }
+        Ok(version)
+    }
+
+    // Also sets the checksum and box data flags, the FEC flag is set once parity has been
+    // written
+    pub fn set_profile(&mut self, profile: &EncodingProfile) -> Result<(), MonFsError> {
+        self.set_layout(&profile.layout)?;
+        self.set_header_field(HEADER_PROFILE, profile.id as u64)?;
+
+        let mut flags = self.get_flags()? & !(PC_FLAG_CHECKSUM | PC_FLAG_BOX_DATA);
+        if profile.mon_checksums {
+            flags |= PC_FLAG_CHECKSUM;
+        }
+        if profile.box_data {
+            flags |= PC_FLAG_BOX_DATA;
+        }
+        self.set_flags(flags)
+    }
+
+    // None when the PC predates profiles or was written with a custom layout
+    pub fn get_profile(&self) -> Result<Option<&'static EncodingProfile>, MonFsError> {
+        if self.get_version()? == 0 {
+            return Ok(None);
+        }
+
+        match self.get_header_field(HEADER_PROFILE)? as u8 {
+            CUSTOM_PROFILE_ID => Ok(None),
+            id => match profile_by_id(id) {
+                Some(profile) => Ok(Some(profile)),
+                None => Err(MonFsError::UnknownProfile(id)),
+            },
+        }
+    }
+
+    // Layout used by the data mons, the header mon always uses the default layout.
+    // Setting a layout directly marks the PC as using a custom profile.
+    pub fn set_layout(&mut self, layout: &MonLayout) -> Result<(), MonFsError> {
+        let run_length = match layout.packing {
+            MonPacking::Bits => 0,
+            MonPacking::MixedRadix(run_length) => {
+                if run_length == 0 || run_length > NUM_OF_DATA_MONS {
+                    return Err(MonFsError::BadRunLength(run_length));
+                }
+                run_length
             }
+        };
+
+        self.set_header_field(HEADER_VERSION, FORMAT_VERSION as u64)?;
+        self.set_header_field(HEADER_PROFILE, CUSTOM_PROFILE_ID as u64)?;
+        self.set_header_field(HEADER_ALPHABET, layout.alphabet as u64)?;
+        self.set_header_field(HEADER_RUN_LENGTH, run_length as u64)?;
+        self.set_header_field(HEADER_CAPTURED_BALL, layout.captured_ball as u64)?;
+        self.set_header_field(HEADER_MARKINGS, layout.markings as u64)?;
+        self.set_header_field(HEADER_NATURE, layout.nature as u64)
+    }
+
+    pub fn get_layout(&self) -> Result<MonLayout, MonFsError> {
+        if let Some(profile) = self.get_profile()? {
+            return Ok(profile.layout);
+        }
+
+        let alphabet_id = self.get_header_field(HEADER_ALPHABET)? as u8;
+        let alphabet = match NameAlphabet::from_repr(alphabet_id) {
+            Some(alphabet) => alphabet,
+            None => return Err(MonFsError::UnknownAlphabet(alphabet_id)),
+        };
+
+        let packing = match self.get_header_field(HEADER_RUN_LENGTH)? as usize {
+            0 => MonPacking::Bits,
+            run_length if run_length <= NUM_OF_DATA_MONS => MonPacking::MixedRadix(run_length),
+            run_length => return Err(MonFsError::BadRunLength(run_length)),
+        };
+
+        Ok(MonLayout {
+            alphabet,
+            packing,
+            captured_ball: self.get_header_field(HEADER_CAPTURED_BALL)? == 1,
+            markings: self.get_header_field(HEADER_MARKINGS)? == 1,
+            nature: self.get_header_field(HEADER_NATURE)? == 1,
+        })
+    }
+
+    fn has_checksums(&self) -> Result<bool, MonFsError> {
+        Ok(self.get_flags()? & PC_FLAG_CHECKSUM != 0)
+    }
+
+    // Bits of each data mon that hold data, rounded up for mixed radix packing
+    pub(crate) fn payload_bit_count(&self) -> Result<usize, MonFsError> {
+        if let MonPacking::MixedRadix(_) = self.get_layout()?.packing {
+            return Ok((u128::BITS - self.mon_radix()?.leading_zeros()) as usize);
+        }
+
+        let bit_count = self.get_layout()?.bit_count();
+        if self.has_checksums()? {
+            Ok(bit_count - CHECKSUM_BITS)
+        } else {
+            Ok(bit_count)
+        }
+    }
+
+    // Values each data mon holds with mixed radix packing, less the checksum if there is one
+    fn mon_radix(&self) -> Result<u128, MonFsError> {
+        let values = self.get_layout()?.possible_values();
+        if self.has_checksums()? {
+            Ok(values >> CHECKSUM_BITS)
+        } else {
+            Ok(values)
         }
-        padding
+    }
+
+    fn index_to_position(index: usize) -> MonPosition {
+        MonPosition {
+            box_index: index / PC_BOX_SIZE,
+            slot: index % PC_BOX_SIZE,
+        }
+    }
+
+    // Position of the mon holding the given bit of the data stream, None for bits held by
+    // the box titles or past the last data mon
+    pub fn mon_position_for_bit(&self, bit: usize) -> Result<Option<MonPosition>, MonFsError> {
+        let bit = match bit.checked_sub(self.box_bytes_used()? * 8) {
+            Some(bit) => bit,
+            None => return Ok(None),
+        };
+
+        // A mixed radix run can only be pinned down to its first mon
+        if let MonPacking::MixedRadix(run_length) = self.get_layout()?.packing {
+            let run_bits = mixed_radix_bit_counts(self.mon_radix()?, run_length)[run_length];
+            return Ok(Some(Self::index_to_position(
+                bit / run_bits * run_length + 1,
+            )));
+        }
+
+        // Data starts after the header mon and permutation table
+        let slots = self.data_slots()?;
+        Ok(slots
+            .get(bit / self.payload_bit_count()?)
+            .map(|slot| Self::index_to_position(*slot)))
+    }
+
+    fn mon_bits(
+        layout: &MonLayout,
+        mon: &BoxMon,
+        index: usize,
+    ) -> Result<BoxMonBitVec, MonFsError> {
+        match layout.mon_to_bits(mon) {
+            Ok(bits) => Ok(bits),
+            Err(err) => Err(MonFsError::BadGameValue {
+                position: Self::index_to_position(index),
+                error: GameValueError::FromGameValue(err),
+            }),
+        }
+    }
+
+    fn mon_value(layout: &MonLayout, mon: &BoxMon, index: usize) -> Result<u128, MonFsError> {
+        match layout.mon_to_value(mon) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(MonFsError::BadGameValue {
+                position: Self::index_to_position(index),
+                error: GameValueError::FromGameValue(err),
+            }),
+        }
+    }
+
+    // A mixed radix mon's digit of its run and whether its checksum matched
+    fn mon_digit(
+        &self,
+        layout: &MonLayout,
+        mon: &BoxMon,
+        index: usize,
+    ) -> Result<(u128, bool), MonFsError> {
+        let value = Self::mon_value(layout, mon, index)?;
+        if !self.has_checksums()? {
+            return Ok((value, true));
+        }
+
+        let digit = value >> CHECKSUM_BITS;
+        let stored = (value & ((1 << CHECKSUM_BITS) - 1)) as u8;
+        Ok((digit, digit_checksum(digit, index) == stored))
+    }
+
+    // Mixed radix runs over the first used data mons as (first index, mons in run)
+    fn runs(run_length: usize, used: usize) -> impl Iterator<Item = (usize, usize)> {
+        (0..used)
+            .step_by(run_length)
+            .map(move |start| (start + 1, run_length.min(used - start)))
+    }
+
+    pub(crate) fn mon_from_payload(
+        &self,
+        index: usize,
+        mut payload: BoxMonBitVec,
+    ) -> Result<BoxMon, MonFsError> {
+        if self.has_checksums()? {
+            let checksum = mon_checksum(&payload, index);
+            for i in 0..CHECKSUM_BITS {
+                payload.0.push(checksum & (1 << i) != 0);
+            }
+        }
+
+        match self.get_layout()?.bits_to_mon(&payload) {
+            Ok(mon) => Ok(mon),
+            Err(err) => Err(MonFsError::BadGameValue {
+                position: Self::index_to_position(index),
+                error: GameValueError::ToGameValue(err),
+            }),
+        }
+    }
+
+    // Checks every data mon against its checksum, reports nothing if checksums are off
+    pub fn verify(&self) -> Result<IntegrityReport, MonFsError> {
+        let mut report = IntegrityReport::default();
+        if !self.has_checksums()? {
+            return Ok(report);
+        }
+
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
+        let radix = self.mon_radix()?;
+        let slots = self.data_slots()?;
+        let used = self.get_empty_offset()? - 1;
+        // The permutation table is checked along with the data
+        let table = 1..=self.permutation_mons()?;
+        for index in table.chain(slots[..used].iter().copied()) {
+            let mon = match &self.mons[index] {
+                Some(mon) => mon,
+                None => break,
+            };
+
+            let valid = match layout.packing {
+                MonPacking::Bits => {
+                    let bits = Self::mon_bits(&layout, mon, index)?;
+                    let stored = bits.chunk(payload_bits, layout.bit_count()).as_u8();
+                    mon_checksum(&bits.chunk(0, payload_bits), index) == stored
+                }
+                MonPacking::MixedRadix(_) => {
+                    let (digit, checksum_matches) = self.mon_digit(&layout, mon, index)?;
+                    digit < radix && checksum_matches
+                }
+            };
+            if !valid {
+                report.suspect.push(Self::index_to_position(index));
+            }
+            report.checked_mons += 1;
+        }
+
+        Ok(report)
+    }
+
+    // Slots and boxes that differ between the two PCs, header included
+    pub fn diff(&self, other: &PC) -> PcDiff {
+        let mut diff = PcDiff::default();
+        for index in 0..self.mons.len().max(other.mons.len()) {
+            if self.mons.get(index).copied().flatten() != other.mons.get(index).copied().flatten() {
+                diff.mons.push(Self::index_to_position(index));
+            }
+        }
+        for box_index in 0..NUM_PC_BOXES {
+            if self.boxes.get(box_index) != other.boxes.get(box_index) {
+                diff.boxes.push(box_index);
+            }
+        }
+
+        diff
     }
 
     pub fn set_mon(&mut self, box_index: usize, mon_index: usize, mon: BoxMon) {
@@ -71,114 +518,706 @@ impl PC {
         self.raw_cache = None;
     }
 
-    fn get_empty_offset(&self) -> usize {
-        for offset in 1..self.mons.len() {
-            if self.mons[offset].is_none() {
-                return offset;
+    pub fn set_box(&mut self, box_index: usize, pc_box: PcBox) -> Result<(), MonFsError> {
+        if self.boxes.len() != NUM_PC_BOXES {
+            return Err(MonFsError::MissingBoxes);
+        }
+        self.boxes[box_index] = pc_box;
+        self.raw_cache = None;
+        Ok(())
+    }
+
+    fn has_box_data(&self) -> Result<bool, MonFsError> {
+        Ok(self.get_flags()? & PC_FLAG_BOX_DATA != 0)
+    }
+
+    // Whole bytes the box titles and wallpapers can hold, 0 unless PC_FLAG_BOX_DATA is set
+    pub fn box_capacity_bytes(&self) -> Result<usize, MonFsError> {
+        if !self.has_box_data()? {
+            return Ok(0);
+        }
+
+        let radix = PcBox::radix(self.get_layout()?.alphabet);
+        Ok(mixed_radix_bit_counts(radix, NUM_PC_BOXES)[NUM_PC_BOXES] / 8)
+    }
+
+    fn box_bytes_used(&self) -> Result<usize, MonFsError> {
+        if !self.has_box_data()? {
+            return Ok(0);
+        }
+
+        Ok(self.get_header_field(HEADER_BOX_BYTES)? as usize)
+    }
+
+    // The boxes are one mixed radix number with the first box least significant
+    fn box_data(&self) -> Result<Vec<u8>, MonFsError> {
+        let used = self.box_bytes_used()?;
+        if used == 0 {
+            return Ok(vec![]);
+        }
+        if self.boxes.len() != NUM_PC_BOXES {
+            return Err(MonFsError::MissingBoxes);
+        }
+
+        let alphabet = self.get_layout()?.alphabet;
+        let mut digits = vec![];
+        for (box_index, pc_box) in self.boxes.iter().enumerate() {
+            match pc_box.to_value(alphabet) {
+                Ok(digit) => digits.push(digit),
+                Err(err) => {
+                    return Err(MonFsError::BadBox {
+                        box_index,
+                        error: GameValueError::FromGameValue(err),
+                    })
+                }
+            }
+        }
+
+        let bits = match digits_to_bits(&digits, PcBox::radix(alphabet), used * 8) {
+            Ok(bits) => bits,
+            Err(err) => {
+                return Err(MonFsError::BadBox {
+                    box_index: NUM_PC_BOXES - 1,
+                    error: GameValueError::ToGameValue(err),
+                })
+            }
+        };
+
+        Ok(bits.to_raw())
+    }
+
+    fn write_box_data(&mut self, data: &[u8]) -> Result<(), MonFsError> {
+        let alphabet = self.get_layout()?.alphabet;
+        let radix = PcBox::radix(alphabet);
+        let mut bits = BoxMonBitVec::new_from_raw(data);
+        bits.0
+            .grow(self.box_capacity_bytes()? * 8 - bits.0.len(), false);
+
+        let digits = match bits_to_digits(&bits, radix, NUM_PC_BOXES) {
+            Ok(digits) => digits,
+            Err(err) => {
+                return Err(MonFsError::BadBox {
+                    box_index: 0,
+                    error: GameValueError::FromGameValue(err),
+                })
+            }
+        };
+
+        let mut boxes = vec![];
+        for (box_index, digit) in digits.into_iter().enumerate() {
+            match PcBox::from_value(digit, alphabet) {
+                Ok(pc_box) => boxes.push(pc_box),
+                Err(err) => {
+                    return Err(MonFsError::BadBox {
+                        box_index,
+                        error: GameValueError::ToGameValue(err),
+                    })
+                }
             }
         }
 
-        return self.mons.len() - 1;
+        self.boxes = boxes;
+        self.raw_cache = None;
+        self.set_header_field(HEADER_BOX_BYTES, data.len() as u64)
     }
 
-    fn get_data(&mut self) -> &BoxMonBitVec {
+    // One past the last data mon in use, data mon i is in slot data_slots()[i - 1]
+    fn get_empty_offset(&self) -> Result<usize, MonFsError> {
+        let slots = self.data_slots()?;
+        let used = slots
+            .iter()
+            .position(|slot| self.mons[*slot].is_none())
+            .unwrap_or(slots.len());
+        Ok(used + 1)
+    }
+
+    fn permutation_mons(&self) -> Result<usize, MonFsError> {
+        Ok(self.get_header_field(HEADER_PERMUTATION_MONS)? as usize)
+    }
+
+    // Slot of each data mon in order, straight after the header unless there is a
+    // permutation table, see set_permutation
+    fn data_slots(&self) -> Result<Vec<usize>, MonFsError> {
+        let table_mons = self.permutation_mons()?;
+        let first = table_mons + 1;
+        let slot_count = NUM_OF_DATA_MONS - table_mons;
+        if table_mons == 0 {
+            return Ok((first..first + slot_count).collect());
+        }
+
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
+        let mut table = BitVec::new();
+        for index in 1..=table_mons {
+            match &self.mons[index] {
+                Some(mon) => table.extend(
+                    Self::mon_bits(&layout, mon, index)?
+                        .0
+                        .iter()
+                        .take(payload_bits),
+                ),
+                None => return Err(MonFsError::BadPermutation),
+            }
+        }
+
+        let table = BoxMonBitVec(table);
+        let entry = |i: usize| {
+            let start = i * PERMUTATION_INDEX_BITS;
+            table.chunk(start, start + PERMUTATION_INDEX_BITS).as_u64() as usize
+        };
+        let runs = entry(0);
+        if (1 + runs * 3) * PERMUTATION_INDEX_BITS > table.0.len() {
+            return Err(MonFsError::BadPermutation);
+        }
+
+        let mut slots = vec![None; slot_count];
+        let mut taken = vec![false; slot_count];
+        for run in 0..runs {
+            let (data_mon, slot, len) =
+                (entry(1 + run * 3), entry(2 + run * 3), entry(3 + run * 3));
+            if data_mon + len > slot_count || slot + len > slot_count {
+                return Err(MonFsError::BadPermutation);
+            }
+            for i in 0..len {
+                if slots[data_mon + i].is_some() || taken[slot + i] {
+                    return Err(MonFsError::BadPermutation);
+                }
+                slots[data_mon + i] = Some(first + slot + i);
+                taken[slot + i] = true;
+            }
+        }
+
+        // Every other data mon takes the next free slot
+        let mut free = (0..slot_count).filter(|slot| !taken[*slot]);
+        Ok(slots
+            .into_iter()
+            .map(|slot| slot.unwrap_or_else(|| first + free.next().unwrap()))
+            .collect())
+    }
+
+    // Moves runs of data mons to other slots, the rest fill the slots left over in order. The
+    // runs are stored in a table in the mons after the header. Only for empty PCs with bits
+    // packing
+    pub fn set_permutation(&mut self, runs: &[SlotRun]) -> Result<(), MonFsError> {
+        if self.get_layout()?.packing != MonPacking::Bits
+            || self.mons[1..].iter().any(Option::is_some)
+        {
+            return Err(MonFsError::BadPermutation);
+        }
+
+        let payload_bits = self.payload_bit_count()?;
+        let table_mons = self.permutation_table_mons(runs.len())?;
+        if table_mons > MAX_PERMUTATION_MONS {
+            return Err(MonFsError::BadPermutation);
+        }
+
+        let mut table = BoxMonBitVec::new(PERMUTATION_INDEX_BITS, runs.len() as u64);
+        for run in runs {
+            for value in [run.data_mon, run.slot, run.len] {
+                let bits = BoxMonBitVec::new(PERMUTATION_INDEX_BITS, value as u64);
+                table.0.extend(bits.0.iter());
+            }
+        }
+        table
+            .0
+            .grow(table_mons * payload_bits - table.0.len(), false);
+
+        let previous = self.mons[0];
+        self.set_header_field(HEADER_PERMUTATION_MONS, table_mons as u64)?;
+        for index in 1..=table_mons {
+            let start = (index - 1) * payload_bits;
+            let payload = table.chunk(start, start + payload_bits);
+            self.mons[index] = Some(self.mon_from_payload(index, payload)?);
+        }
+        self.raw_cache = None;
+
+        // Overlapping or out of range runs are caught the same way as reading them back
+        if let Err(err) = self.data_slots() {
+            self.mons[0] = previous;
+            for mon in self.mons.iter_mut().skip(1) {
+                *mon = None;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    // Mons the permutation table takes up for the given number of runs
+    pub(crate) fn permutation_table_mons(&self, runs: usize) -> Result<usize, MonFsError> {
+        if runs == 0 {
+            return Ok(0);
+        }
+
+        let table_bits = (1 + runs * 3) * PERMUTATION_INDEX_BITS;
+        Ok(table_bits.div_ceil(self.payload_bit_count()?))
+    }
+
+    // Payload of every data mon in use in data order, before the padding is cut off
+    pub(crate) fn data_payloads(&self) -> Result<Vec<BoxMonBitVec>, MonFsError> {
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
+        let slots = self.data_slots()?;
+
+        let mut payloads = vec![];
+        for slot in &slots[..self.get_empty_offset()? - 1] {
+            if let Some(mon) = &self.mons[*slot] {
+                payloads.push(Self::mon_bits(&layout, mon, *slot)?.chunk(0, payload_bits));
+            }
+        }
+        Ok(payloads)
+    }
+
+    // The same header with no data or permutation table, ready to be written again
+    pub(crate) fn cleared(&self) -> Result<PC, MonFsError> {
+        let mut pc = PC::new();
+        pc.mons[0] = self.mons[0];
+        pc.set_padding_amount(0)?;
+        pc.set_header_field(HEADER_PERMUTATION_MONS, 0)?;
+        if pc.has_box_data()? {
+            pc.set_header_field(HEADER_BOX_BYTES, 0)?;
+        }
+        Ok(pc)
+    }
+
+    fn get_data(&mut self) -> Result<&BoxMonBitVec, MonFsError> {
         if self.raw_cache.is_none() {
-            let last_mon_index = self.get_empty_offset();
-            let mut fat: BitVec = BitVec::new();
-            for i in 1..last_mon_index + 1 {
-                // Skip the padding mon
-                match self.mons[i] {
-                    Some(mon) => {
-                        let bits = mon.game_value_to_bits().unwrap();
-                        if i == last_mon_index - 1 {
-                            let padding_amount = self.get_padding_amount();
-                            for i in 0..BoxMon::bit_count() - padding_amount as usize {
-                                fat.push(bits.0[i]);
-                            }
-                        } else {
-                            fat.extend(bits.0);
+            let mut data = BoxMonBitVec::new_from_raw(&self.box_data()?);
+            data.0.extend(self.mon_data()?.0.iter());
+            self.raw_cache = Some(data);
+        }
+
+        Ok(self.raw_cache.get_or_insert_with(BoxMonBitVec::default))
+    }
+
+    // Everything the PC holds, the same bytes Read returns
+    pub(crate) fn data(&mut self) -> Result<Vec<u8>, MonFsError> {
+        Ok(self.get_data()?.to_raw())
+    }
+
+    fn mon_data(&self) -> Result<BoxMonBitVec, MonFsError> {
+        match self.get_layout()?.packing {
+            MonPacking::Bits => self.bits_data(),
+            MonPacking::MixedRadix(run_length) => self.mixed_radix_data(run_length),
+        }
+    }
+
+    fn bits_data(&self) -> Result<BoxMonBitVec, MonFsError> {
+        let last_mon_index = self.get_empty_offset()?;
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
+        let slots = self.data_slots()?;
+        let mut fat: BitVec = BitVec::new();
+        for i in 1..last_mon_index {
+            // Skip the padding mon
+            match &self.mons[slots[i - 1]] {
+                Some(mon) => {
+                    let bits = Self::mon_bits(&layout, mon, slots[i - 1])?;
+                    if i == last_mon_index - 1 {
+                        let padding_amount = self.get_padding_amount()?;
+                        for i in 0..payload_bits - padding_amount as usize {
+                            fat.push(bits.0[i]);
                         }
+                    } else {
+                        fat.extend(bits.0.iter().take(payload_bits));
                     }
-                    None => break,
                 }
+                None => break,
             }
+        }
+
+        Ok(BoxMonBitVec(fat))
+    }
+
+    fn mixed_radix_data(&self, run_length: usize) -> Result<BoxMonBitVec, MonFsError> {
+        let layout = self.get_layout()?;
+        let radix = self.mon_radix()?;
+        let run_bits = mixed_radix_bit_counts(radix, run_length);
+        let used = self.get_empty_offset()? - 1;
+        let padding = self.get_padding_amount()? as usize;
 
-            self.raw_cache = Some(BoxMonBitVec(fat));
+        let mut fat = BitVec::new();
+        for (start, mons) in Self::runs(run_length, used) {
+            let mut digits = vec![];
+            for (mon, index) in self.mons[start..start + mons].iter().flatten().zip(start..) {
+                digits.push(self.mon_digit(&layout, mon, index)?.0);
+            }
+
+            let bits = match digits_to_bits(&digits, radix, run_bits[mons]) {
+                Ok(bits) => bits,
+                Err(err) => {
+                    return Err(MonFsError::BadGameValue {
+                        position: Self::index_to_position(start),
+                        error: GameValueError::ToGameValue(err),
+                    })
+                }
+            };
+
+            // Only the final run has padding
+            let keep = if start + mons > used {
+                run_bits[mons].saturating_sub(padding)
+            } else {
+                run_bits[mons]
+            };
+            fat.extend(bits.0.iter().take(keep));
         }
 
-        self.raw_cache.as_ref().unwrap()
+        Ok(BoxMonBitVec(fat))
     }
 
-    pub fn remaining_bytes(&self) -> usize {
-        let current_offset = self.get_empty_offset();
-        let remaining_bytes = (NUM_OF_DATA_MONS - (current_offset - 1)) * BoxMon::byte_count();
-        remaining_bytes
+    // Runs can't be extended in place so writing repacks all the data already in the mons
+    fn write_mixed_radix(&mut self, run_length: usize, buf: &[u8]) -> Result<(), MonFsError> {
+        let remaining_bytes = self.remaining_bytes()?;
+        if remaining_bytes < buf.len() {
+            return Err(MonFsError::CapacityExceeded {
+                have: remaining_bytes,
+                need: buf.len(),
+            });
+        }
+
+        let mut data = self.mon_data()?.to_raw();
+        data.extend_from_slice(buf);
+        let bits = BoxMonBitVec::new_from_raw(&data);
+
+        let layout = self.get_layout()?;
+        let radix = self.mon_radix()?;
+        let run_bits = mixed_radix_bit_counts(radix, run_length);
+        let checksums = self.has_checksums()?;
+
+        for mon in self.mons.iter_mut().skip(1) {
+            *mon = None;
+        }
+        self.raw_cache = None;
+
+        let mut offset = 0;
+        let mut start = 1;
+        let mut padding = 0;
+        while offset < bits.0.len() {
+            let left = bits.0.len() - offset;
+            let mut mons = run_length.min(NUM_OF_DATA_MONS + 1 - start);
+            // The final run only uses as many mons as the rest of the data needs
+            while mons > 1 && run_bits[mons - 1] >= left {
+                mons -= 1;
+            }
+
+            let end = (offset + run_bits[mons]).min(bits.0.len());
+            padding = run_bits[mons] - (end - offset);
+
+            let digits = match bits_to_digits(&bits.chunk(offset, end), radix, mons) {
+                Ok(digits) => digits,
+                Err(err) => {
+                    return Err(MonFsError::BadGameValue {
+                        position: Self::index_to_position(start),
+                        error: GameValueError::FromGameValue(err),
+                    })
+                }
+            };
+
+            for (digit, index) in digits.into_iter().zip(start..) {
+                self.mons[index] = Some(Self::mon_from_digit(&layout, checksums, digit, index)?);
+            }
+
+            offset = end;
+            start += mons;
+        }
+
+        self.set_padding_amount(padding as u8)
+    }
+
+    fn mon_from_digit(
+        layout: &MonLayout,
+        checksums: bool,
+        digit: u128,
+        index: usize,
+    ) -> Result<BoxMon, MonFsError> {
+        let value = if checksums {
+            (digit << CHECKSUM_BITS) | digit_checksum(digit, index) as u128
+        } else {
+            digit
+        };
+
+        match layout.value_to_mon(value) {
+            Ok(mon) => Ok(mon),
+            Err(err) => Err(MonFsError::BadGameValue {
+                position: Self::index_to_position(index),
+                error: GameValueError::ToGameValue(err),
+            }),
+        }
+    }
+
+    // Overwrites data already in the PC starting at the given byte, only the box titles and
+    // mons holding those bytes are re-encoded
+    pub fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<(), MonFsError> {
+        let len = self.get_data()?.0.len() / 8;
+        if offset + buf.len() > len {
+            return Err(MonFsError::OutOfRange {
+                end: offset + buf.len(),
+                len,
+            });
+        }
+
+        let box_bytes = self.box_bytes_used()?;
+        if offset < box_bytes {
+            let mut data = self.box_data()?;
+            let end = (offset + buf.len()).min(box_bytes);
+            data[offset..end].copy_from_slice(&buf[..end - offset]);
+            self.write_box_data(&data)?;
+        }
+
+        let skip = box_bytes.saturating_sub(offset).min(buf.len());
+        let start = (offset + skip).saturating_sub(box_bytes) * 8;
+        let bits = BoxMonBitVec::new_from_raw(&buf[skip..]);
+        if !bits.0.is_empty() {
+            match self.get_layout()?.packing {
+                MonPacking::Bits => self.patch_bits(start, &bits)?,
+                MonPacking::MixedRadix(run_length) => {
+                    self.patch_mixed_radix(run_length, start, &bits)?
+                }
+            }
+        }
+
+        self.raw_cache = None;
+        Ok(())
+    }
+
+    // Replaces the mon data from the given bit onwards with bits
+    fn patch_bits(&mut self, start: usize, bits: &BoxMonBitVec) -> Result<(), MonFsError> {
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
+        let end = start + bits.0.len();
+        let slots = self.data_slots()?;
+
+        let touched = start / payload_bits..=(end - 1) / payload_bits;
+        for (data_mon, slot) in touched.clone().zip(slots[touched].iter().copied()) {
+            let mut payload = match &self.mons[slot] {
+                Some(mon) => Self::mon_bits(&layout, mon, slot)?.chunk(0, payload_bits),
+                None => return Err(MonFsError::MissingMon(Self::index_to_position(slot))),
+            };
+
+            let mon_start = data_mon * payload_bits;
+            for i in start.max(mon_start)..end.min(mon_start + payload_bits) {
+                payload.0.set(i - mon_start, bits.0[i - start]);
+            }
+            self.mons[slot] = Some(self.mon_from_payload(slot, payload)?);
+        }
+
+        Ok(())
+    }
+
+    // Like patch_bits but every run touched is repacked
+    fn patch_mixed_radix(
+        &mut self,
+        run_length: usize,
+        start: usize,
+        bits: &BoxMonBitVec,
+    ) -> Result<(), MonFsError> {
+        let layout = self.get_layout()?;
+        let radix = self.mon_radix()?;
+        let checksums = self.has_checksums()?;
+        let run_bits = mixed_radix_bit_counts(radix, run_length);
+        let end = start + bits.0.len();
+
+        let mut run_start = 0;
+        for (first, mons) in Self::runs(run_length, self.get_empty_offset()? - 1) {
+            let run_end = run_start + run_bits[mons];
+            if run_end > start && run_start < end {
+                let mut digits = vec![];
+                for index in first..first + mons {
+                    let mon = match &self.mons[index] {
+                        Some(mon) => mon,
+                        None => return Err(MonFsError::MissingMon(Self::index_to_position(index))),
+                    };
+                    digits.push(self.mon_digit(&layout, mon, index)?.0);
+                }
+
+                let mut run = match digits_to_bits(&digits, radix, run_bits[mons]) {
+                    Ok(run) => run,
+                    Err(err) => {
+                        return Err(MonFsError::BadGameValue {
+                            position: Self::index_to_position(first),
+                            error: GameValueError::ToGameValue(err),
+                        })
+                    }
+                };
+                for i in start.max(run_start)..end.min(run_end) {
+                    run.0.set(i - run_start, bits.0[i - start]);
+                }
+
+                let digits = match bits_to_digits(&run, radix, mons) {
+                    Ok(digits) => digits,
+                    Err(err) => {
+                        return Err(MonFsError::BadGameValue {
+                            position: Self::index_to_position(first),
+                            error: GameValueError::FromGameValue(err),
+                        })
+                    }
+                };
+                for (digit, index) in digits.into_iter().zip(first..) {
+                    self.mons[index] =
+                        Some(Self::mon_from_digit(&layout, checksums, digit, index)?);
+                }
+            }
+            run_start = run_end;
+        }
+
+        Ok(())
+    }
+
+    // Bits the first used data mons hold with mixed radix packing
+    fn mixed_radix_bit_count(&self, run_length: usize, used: usize) -> Result<usize, MonFsError> {
+        let run_bits = mixed_radix_bit_counts(self.mon_radix()?, run_length);
+        Ok(Self::runs(run_length, used)
+            .map(|(_, mons)| run_bits[mons])
+            .sum())
+    }
+
+    pub fn usage(&self) -> Result<PcUsage, MonFsError> {
+        let used_mons = self.get_empty_offset()? - 1;
+        Ok(PcUsage {
+            version: self.get_version()?,
+            flags: self.get_flags()?,
+            profile: self.get_profile()?.map(|profile| profile.name.to_string()),
+            used_mons,
+            data_mons: self.data_slots()?.len(),
+            permutation_mons: self.permutation_mons()?,
+            padding_bits: match used_mons {
+                0 => 0,
+                _ => self.get_padding_amount()? as usize,
+            },
+            box_bytes_used: self.box_bytes_used()?,
+            remaining_bytes: self.remaining_bytes()?,
+        })
+    }
+
+    pub fn remaining_bytes(&self) -> Result<usize, MonFsError> {
+        let box_bytes = self.box_capacity_bytes()? - self.box_bytes_used()?;
+        Ok(box_bytes + self.remaining_mon_bytes()?)
+    }
+
+    fn remaining_mon_bytes(&self) -> Result<usize, MonFsError> {
+        let current_offset = self.get_empty_offset()?;
+        if let MonPacking::MixedRadix(run_length) = self.get_layout()?.packing {
+            let capacity = self.mixed_radix_bit_count(run_length, NUM_OF_DATA_MONS)?;
+            let mut used = self.mixed_radix_bit_count(run_length, current_offset - 1)?;
+            if used > 0 {
+                used -= self.get_padding_amount()? as usize;
+            }
+            return Ok((capacity / 8).saturating_sub(used / 8));
+        }
+
+        let slots = self.data_slots()?.len();
+        Ok((slots - (current_offset - 1)) * self.payload_bit_count()? / 8)
     }
 }
 
+fn digit_checksum(digit: u128, index: usize) -> u8 {
+    mon_checksum(&BoxMonBitVec::new_from_raw(&digit.to_le_bytes()), index)
+}
+
+// CRC-4 over the payload and the mon's index so swapped mons are caught too
+fn mon_checksum(payload: &BoxMonBitVec, index: usize) -> u8 {
+    let index_bits = (0..16).map(|i| index & (1 << i) != 0);
+
+    let mut crc = 0;
+    for bit in payload.0.iter().chain(index_bits) {
+        let top = (crc >> (CHECKSUM_BITS - 1)) & 1 == 1;
+        crc = (crc << 1) & ((1 << CHECKSUM_BITS) - 1);
+        if top != bit {
+            crc ^= CHECKSUM_POLY;
+        }
+    }
+    crc
+}
+
 impl std::io::Write for PC {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // The box titles are filled before any mons
+        let box_space = self.box_capacity_bytes()? - self.box_bytes_used()?;
+        if box_space > 0 && !buf.is_empty() {
+            let remaining_bytes = self.remaining_bytes()?;
+            if remaining_bytes < buf.len() {
+                return Err(MonFsError::CapacityExceeded {
+                    have: remaining_bytes,
+                    need: buf.len(),
+                }
+                .into());
+            }
+
+            let take = box_space.min(buf.len());
+            let mut data = self.box_data()?;
+            data.extend_from_slice(&buf[..take]);
+            self.write_box_data(&data)?;
+
+            if take < buf.len() {
+                self.write(&buf[take..])?;
+            }
+            return Ok(buf.len());
+        }
+
+        if let MonPacking::MixedRadix(run_length) = self.get_layout()?.packing {
+            self.write_mixed_radix(run_length, buf)?;
+            return Ok(buf.len());
+        }
+
         let bits: BoxMonBitVec = BoxMonBitVec::new_from_raw(buf);
 
         assert_eq!(buf.len(), bits.0.len() / 8);
 
         let mut offset = 0;
 
-        let mut current_offset = self.get_empty_offset();
-        if self.remaining_bytes() < buf.len() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Not enough space in PC have {} need {}",
-                    self.remaining_bytes(),
-                    buf.len()
-                ),
-            ));
+        let mut current_offset = self.get_empty_offset()?;
+        let slots = self.data_slots()?;
+        let remaining_bytes = self.remaining_bytes()?;
+        if remaining_bytes < buf.len() {
+            return Err(MonFsError::CapacityExceeded {
+                have: remaining_bytes,
+                need: buf.len(),
+            }
+            .into());
         }
 
         self.raw_cache = None;
+        let layout = self.get_layout()?;
+        let payload_bits = self.payload_bit_count()?;
 
         if current_offset > 1 {
-            let padding_amount = self.get_padding_amount();
-            let mut last_mon_bits = self.mons[current_offset - 1]
-                .unwrap()
-                .game_value_to_bits()
-                .unwrap();
+            // Fill the padding at the end of the last mon before starting new ones
+            let last = slots[current_offset - 2];
+            let padding_amount = self.get_padding_amount()? as usize;
+            let mut payload = match &self.mons[last] {
+                Some(mon) => Self::mon_bits(&layout, mon, last)?.chunk(0, payload_bits),
+                None => return Err(MonFsError::MissingMon(Self::index_to_position(last)).into()),
+            };
 
-            let start_offset = BoxMon::bit_count() - padding_amount as usize;
-            let end_offset = BoxMon::bit_count().min(bits.0.len());
-            // Replace padding data with
-            for i in start_offset..end_offset {
-                last_mon_bits.0.set(i, bits.0[i]);
+            let start_offset = payload_bits - padding_amount;
+            offset = padding_amount.min(bits.0.len());
+            for i in 0..offset {
+                payload.0.set(start_offset + i, bits.0[i]);
             }
+            self.mons[last] = Some(self.mon_from_payload(last, payload)?);
+            self.set_padding_amount((padding_amount - offset) as u8)?;
 
-            if padding_amount as usize > bits.0.len() {
-                self.set_padding_amount(padding_amount - bits.0.len() as u8);
+            if offset == bits.0.len() {
                 return Ok(buf.len());
             }
-
-            self.set_padding_amount(0);
-
-            // Last mon padding data has been filled now we can treat it as a normal mon
-            current_offset += 1;
         }
 
         loop {
-            let end_offset = (offset + BoxMon::bit_count()).min(bits.0.len());
+            let end_offset = (offset + payload_bits).min(bits.0.len());
 
             let mut chunk = bits.chunk(offset, end_offset);
-            let surplus_bits = BoxMon::bit_count() - chunk.0.len();
-            while chunk.0.len() < BoxMon::bit_count() {
+            let surplus_bits = payload_bits - chunk.0.len();
+            while chunk.0.len() < payload_bits {
                 chunk.0.push(false);
             }
 
-            let mon = BoxMon::bits_to_game_value(&chunk).unwrap();
-            self.mons[current_offset] = Some(mon);
+            let slot = slots[current_offset - 1];
+            self.mons[slot] = Some(self.mon_from_payload(slot, chunk)?);
             current_offset += 1;
 
             let amount_left = bits.0.len() - end_offset;
             if amount_left == 0 {
-                self.set_padding_amount(surplus_bits as u8);
+                self.set_padding_amount(surplus_bits as u8)?;
 
                 return Ok(end_offset / 8);
             }
@@ -194,16 +1233,17 @@ impl std::io::Write for PC {
 impl std::io::Read for PC {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let current_read_offset = self.current_read_offset;
-        let data = self.get_data();
+        let data = self.get_data()?;
 
-        let end = (current_read_offset + buf.len()).min(data.0.len());
+        let end = (current_read_offset + buf.len()).min(data.0.len() / 8);
+        if end <= current_read_offset {
+            return Ok(0);
+        }
 
         let fat = data.chunk(current_read_offset * 8, end * 8);
         let fat = fat.to_raw();
 
-        for i in 0..fat.len() {
-            buf[i] = fat[i];
-        }
+        buf[..fat.len()].copy_from_slice(&fat);
 
         self.current_read_offset += fat.len();
 
@@ -211,10 +1251,36 @@ impl std::io::Read for PC {
     }
 }
 
+// Only moves where Read reads from, Write always appends and write_at overwrites
+impl std::io::Seek for PC {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let (base, delta) = match pos {
+            std::io::SeekFrom::Start(offset) => (0, offset as i64),
+            std::io::SeekFrom::End(delta) => (self.get_data()?.0.len() / 8, delta),
+            std::io::SeekFrom::Current(delta) => (self.current_read_offset, delta),
+        };
+
+        match (base as i64).checked_add(delta) {
+            Some(offset) if offset >= 0 => {
+                self.current_read_offset = offset as usize;
+                Ok(offset as u64)
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before the start of the PC",
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::mon_field::ByteCount;
+    use crate::mon_species::BoxMonSpecies;
     use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
     use std::io::Write;
 
     #[test]
@@ -223,7 +1289,7 @@ mod test {
 
         let huge_amount_of_data = include_bytes!("../../test_assets/ricky.webp").to_vec();
 
-        pc.write(&huge_amount_of_data).unwrap();
+        pc.write_all(&huge_amount_of_data).unwrap();
 
         let mut buf = Vec::new();
         pc.read_to_end(&mut buf).unwrap();
@@ -231,15 +1297,42 @@ mod test {
         assert_eq!(huge_amount_of_data, buf);
     }
 
+    fn whole_pc_mixed_radix() -> PC {
+        let mut pc = PC::new();
+        pc.set_layout(&MonLayout {
+            packing: MonPacking::MixedRadix(NUM_OF_DATA_MONS),
+            ..Default::default()
+        })
+        .unwrap();
+        pc
+    }
+
     #[test]
     fn ensure_normal_amount_of_bits() {
         assert_eq!(PC::bit_count() % 8, 0);
+        assert_eq!(PC::byte_count(), 3553);
+    }
+
+    #[test]
+    fn whole_pc_run_holds_the_information_content() {
+        // 4 species * 2 genders * 64^10 names * 33 items is a little over 68 bits a mon
+        let pc = whole_pc_mixed_radix();
+        assert_eq!(pc.remaining_bytes().unwrap(), 28442 / 8);
+        assert!(pc.remaining_bytes().unwrap() > PC::byte_count());
+
+        let mut pc = whole_pc_mixed_radix();
+        let data: Vec<u8> = (0..28442 / 8).map(|i| (i % 255) as u8).collect();
+        pc.write_all(&data).unwrap();
+
+        let mut buf = Vec::new();
+        pc.read_to_end(&mut buf).unwrap();
+        assert_eq!(data, buf);
     }
 
     #[test]
     fn byte_count_accuracy() {
         let pc = PC::new();
-        assert_eq!(PC::byte_count(), pc.remaining_bytes());
+        assert_eq!(PC::byte_count(), pc.remaining_bytes().unwrap());
     }
 
     #[test]
@@ -247,15 +1340,443 @@ mod test {
         let mut pc = PC::new();
 
         let mut data = vec![0; PC::byte_count()];
-        for i in 0..data.len() {
-            data[i] = (i % 255) as u8;
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 255) as u8;
         }
 
-        pc.write(&data).unwrap();
+        pc.write_all(&data).unwrap();
 
         let mut buf = Vec::new();
         pc.read_to_end(&mut buf).unwrap();
 
         assert_eq!(data, buf);
     }
+
+    #[test]
+    fn flags_survive_padding_changes() {
+        let mut pc = PC::new();
+        pc.set_flags(PC_FLAG_FEC).unwrap();
+        pc.write_all(&[1, 2, 3, 4]).unwrap();
+
+        assert_eq!(pc.get_flags().unwrap(), PC_FLAG_FEC);
+
+        let mut buf = Vec::new();
+        pc.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn checksums_find_misread_mons() {
+        let mut pc = PC::new();
+        pc.set_flags(PC_FLAG_CHECKSUM).unwrap();
+
+        let data = include_bytes!("../../test_assets/ricky.webp").to_vec();
+        pc.write_all(&data).unwrap();
+
+        assert_eq!(pc.verify().unwrap().suspect, vec![]);
+
+        let mut buf = Vec::new();
+        pc.clone().read_to_end(&mut buf).unwrap();
+        assert_eq!(data, buf);
+
+        let mut mon = pc.mons[2 * PC_BOX_SIZE + 17].unwrap();
+        mon.species = BoxMonSpecies::from_repr(mon.species as u8 ^ 1).unwrap();
+        pc.set_mon(2, 17, mon);
+
+        // Swapped mons are caught as well
+        pc.mons.swap(2, 3);
+
+        let report = pc.verify().unwrap();
+        assert_eq!(report.checked_mons, pc.get_empty_offset().unwrap() - 1);
+        assert_eq!(
+            report.suspect,
+            vec![
+                MonPosition {
+                    box_index: 0,
+                    slot: 2
+                },
+                MonPosition {
+                    box_index: 0,
+                    slot: 3
+                },
+                MonPosition {
+                    box_index: 2,
+                    slot: 17
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checksums_fill_pc() {
+        let mut pc = PC::new();
+        pc.set_flags(PC_FLAG_CHECKSUM).unwrap();
+        assert_eq!(pc.remaining_bytes().unwrap(), NUM_OF_DATA_MONS * 64 / 8);
+
+        let data: Vec<u8> = (0..pc.remaining_bytes().unwrap())
+            .map(|i| (i % 251) as u8)
+            .collect();
+        pc.write_all(&data).unwrap();
+
+        let mut buf = Vec::new();
+        pc.read_to_end(&mut buf).unwrap();
+        assert_eq!(data, buf);
+    }
+
+    #[test]
+    fn full_alphabet_fills_more() {
+        let mut pc = PC::new();
+        pc.set_layout(&MonLayout {
+            alphabet: NameAlphabet::Full,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(pc.remaining_bytes().unwrap() > PC::byte_count());
+
+        let data: Vec<u8> = (0..pc.remaining_bytes().unwrap())
+            .map(|i| (i * 31 % 256) as u8)
+            .collect();
+        pc.write_all(&data).unwrap();
+        assert!(pc.mons.iter().flatten().any(|mon| mon
+            .name
+            .characters()
+            .contains(&crate::mon_name::BoxMonCharacter::Space)));
+
+        let mut buf = Vec::new();
+        pc.read_to_end(&mut buf).unwrap();
+        assert_eq!(data, buf);
+    }
+
+    #[test]
+    fn mixed_radix_runs_round_trip() {
+        let data = include_bytes!("../../test_assets/ricky.webp").to_vec();
+        for run_length in [1, 2, 7, 30] {
+            for flags in [0, PC_FLAG_CHECKSUM] {
+                let mut pc = PC::new();
+                pc.set_flags(flags).unwrap();
+                pc.set_layout(&MonLayout {
+                    alphabet: NameAlphabet::Full,
+                    packing: MonPacking::MixedRadix(run_length),
+                    captured_ball: true,
+                    markings: true,
+                    nature: true,
+                })
+                .unwrap();
+
+                // Appending repacks the runs already written
+                pc.write_all(&data[..100]).unwrap();
+                pc.write_all(&data[100..]).unwrap();
+                assert_eq!(pc.verify().unwrap().suspect, vec![]);
+
+                let mut buf = Vec::new();
+                pc.read_to_end(&mut buf).unwrap();
+                assert_eq!(data, buf);
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_radix_checksums_find_misread_mons() {
+        let mut pc = PC::new();
+        pc.set_flags(PC_FLAG_CHECKSUM).unwrap();
+        pc.set_layout(&MonLayout {
+            packing: MonPacking::MixedRadix(5),
+            ..Default::default()
+        })
+        .unwrap();
+        pc.write_all(include_bytes!("../../test_assets/ricky.webp"))
+            .unwrap();
+
+        let mut mon = pc.mons[PC_BOX_SIZE + 4].unwrap();
+        mon.species = BoxMonSpecies::from_repr(mon.species as u8 ^ 1).unwrap();
+        pc.set_mon(1, 4, mon);
+
+        assert_eq!(
+            pc.verify().unwrap().suspect,
+            vec![MonPosition {
+                box_index: 1,
+                slot: 4
+            }]
+        );
+        assert_eq!(
+            pc.mon_position_for_bit(0).unwrap(),
+            Some(MonPosition {
+                box_index: 0,
+                slot: 1
+            })
+        );
+    }
+
+    #[test]
+    fn mixed_radix_rejects_too_much_data() {
+        let mut pc = whole_pc_mixed_radix();
+        let capacity = pc.remaining_bytes().unwrap();
+        pc.write_all(&[1; 100]).unwrap();
+        let remaining = pc.remaining_bytes().unwrap();
+        assert_eq!(remaining, capacity - 100);
+        assert!(pc.write_all(&vec![0; remaining + 1]).is_err());
+    }
+
+    #[test]
+    fn header_carries_version_and_profile() {
+        let mut pc = PC::new();
+        assert_eq!(pc.get_version().unwrap(), 0);
+        assert_eq!(pc.get_profile().unwrap(), None);
+
+        let dense = crate::profile::profile_by_name("dense").unwrap();
+        pc.set_profile(dense).unwrap();
+        pc.set_padding_amount(3).unwrap();
+        assert_eq!(pc.get_version().unwrap(), FORMAT_VERSION);
+        assert_eq!(pc.get_profile().unwrap(), Some(dense));
+        assert_eq!(pc.get_layout().unwrap(), dense.layout);
+
+        pc.set_layout(&MonLayout::default()).unwrap();
+        assert_eq!(pc.get_profile().unwrap(), None);
+        assert_eq!(pc.get_layout().unwrap(), MonLayout::default());
+
+        pc.set_header_field(HEADER_PROFILE, 200).unwrap();
+        assert!(matches!(
+            pc.get_layout(),
+            Err(MonFsError::UnknownProfile(200))
+        ));
+
+        pc.set_header_field(HEADER_VERSION, FORMAT_VERSION as u64 + 1)
+            .unwrap();
+        assert!(matches!(
+            pc.get_layout(),
+            Err(MonFsError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn bad_layouts_say_what_is_wrong() {
+        let mut pc = PC::new();
+        assert!(matches!(
+            pc.set_layout(&MonLayout {
+                packing: MonPacking::MixedRadix(0),
+                ..Default::default()
+            }),
+            Err(MonFsError::BadRunLength(0))
+        ));
+
+        pc.set_layout(&MonLayout::default()).unwrap();
+        pc.set_header_field(HEADER_ALPHABET, 3).unwrap();
+        assert!(matches!(
+            pc.get_layout(),
+            Err(MonFsError::UnknownAlphabet(3))
+        ));
+
+        pc.set_header_field(HEADER_ALPHABET, 0).unwrap();
+        pc.set_header_field(HEADER_RUN_LENGTH, 511).unwrap();
+        assert!(matches!(
+            pc.get_layout(),
+            Err(MonFsError::BadRunLength(511))
+        ));
+    }
+
+    #[test]
+    fn bit_positions_map_to_slots() {
+        let pc = PC::new();
+        let bits_per_mon = BoxMon::bit_count();
+        assert_eq!(
+            pc.mon_position_for_bit(0).unwrap(),
+            Some(MonPosition {
+                box_index: 0,
+                slot: 1
+            })
+        );
+        assert_eq!(
+            pc.mon_position_for_bit(bits_per_mon * 29).unwrap(),
+            Some(MonPosition {
+                box_index: 1,
+                slot: 0
+            })
+        );
+    }
+
+    #[test]
+    fn box_titles_hold_the_start_of_the_data() {
+        let data = include_bytes!("../../test_assets/ricky.webp").to_vec();
+        for layout in [
+            MonLayout::default(),
+            whole_pc_mixed_radix().get_layout().unwrap(),
+        ] {
+            let mut plain = PC::new();
+            plain.set_layout(&layout).unwrap();
+            let mut pc = plain.clone();
+            pc.set_flags(PC_FLAG_BOX_DATA).unwrap();
+
+            let box_bytes = pc.box_capacity_bytes().unwrap();
+            assert_eq!(plain.box_capacity_bytes().unwrap(), 0);
+            assert_eq!(
+                pc.remaining_bytes().unwrap(),
+                plain.remaining_bytes().unwrap() + box_bytes
+            );
+
+            // Small writes stay in the boxes then spill over into the mons
+            pc.write_all(&data[..10]).unwrap();
+            assert_eq!(pc.boxes.len(), NUM_PC_BOXES);
+            assert!(pc.mons[1].is_none());
+            pc.write_all(&data[10..box_bytes + 20]).unwrap();
+            assert!(pc.mons[1].is_some());
+            assert_eq!(pc.mon_position_for_bit(box_bytes * 8 - 1).unwrap(), None);
+            assert_eq!(
+                pc.mon_position_for_bit(box_bytes * 8).unwrap(),
+                Some(MonPosition {
+                    box_index: 0,
+                    slot: 1
+                })
+            );
+
+            let mut buf = Vec::new();
+            pc.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, data[..box_bytes + 20]);
+        }
+
+        // 52 bits a box with the legacy alphabet
+        let mut pc = PC::new();
+        pc.set_flags(PC_FLAG_BOX_DATA).unwrap();
+        assert_eq!(pc.box_capacity_bytes().unwrap(), 91);
+    }
+
+    #[test]
+    fn appending_fills_the_last_mon() {
+        let data = include_bytes!("../../test_assets/ricky.webp").to_vec();
+        for flags in [0, PC_FLAG_CHECKSUM] {
+            let mut pc = PC::new();
+            pc.set_flags(flags).unwrap();
+            for chunk in data[..500].chunks(3) {
+                pc.write_all(chunk).unwrap();
+            }
+            assert_eq!(pc.verify().unwrap().suspect, vec![]);
+
+            let mut buf = Vec::new();
+            pc.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, data[..500]);
+        }
+    }
+
+    #[test]
+    fn seek_and_overwrite_in_place() {
+        let data: Vec<u8> = (0..2000).map(|i| (i * 7 % 251) as u8).collect();
+        let patch = [0xAB; 40];
+        for (flags, layout) in [
+            (0, MonLayout::default()),
+            (PC_FLAG_CHECKSUM | PC_FLAG_BOX_DATA, MonLayout::default()),
+            (
+                PC_FLAG_CHECKSUM,
+                MonLayout {
+                    packing: MonPacking::MixedRadix(7),
+                    ..Default::default()
+                },
+            ),
+            (
+                PC_FLAG_BOX_DATA,
+                whole_pc_mixed_radix().get_layout().unwrap(),
+            ),
+        ] {
+            let mut pc = PC::new();
+            pc.set_flags(flags).unwrap();
+            pc.set_layout(&layout).unwrap();
+            pc.write_all(&data[..2000]).unwrap();
+
+            let mut buf = [0; 10];
+            pc.seek(SeekFrom::Start(1500)).unwrap();
+            pc.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, data[1500..1510]);
+            assert_eq!(pc.seek(SeekFrom::Current(-20)).unwrap(), 1490);
+            assert_eq!(pc.seek(SeekFrom::End(-5)).unwrap(), 1995);
+            assert!(pc.seek(SeekFrom::Current(-2000)).is_err());
+
+            let before = pc.mons.clone();
+            let mut expected = data[..2000].to_vec();
+            for offset in [0, 80, 1000, 1960] {
+                pc.write_at(offset, &patch).unwrap();
+                expected[offset..offset + patch.len()].copy_from_slice(&patch);
+            }
+            assert_eq!(pc.verify().unwrap().suspect, vec![]);
+
+            // Only the mons holding the patched bytes change, unless they are all one run
+            let changed = before.iter().zip(&pc.mons).filter(|(a, b)| a != b).count();
+            if layout.packing != MonPacking::MixedRadix(NUM_OF_DATA_MONS) {
+                assert!(changed > 0 && changed < 150);
+            }
+
+            let mut buf = Vec::new();
+            pc.rewind().unwrap();
+            pc.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, expected);
+
+            assert!(matches!(
+                pc.write_at(1990, &patch),
+                Err(MonFsError::OutOfRange {
+                    end: 2030,
+                    len: 2000
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn permutation_table_moves_data_mons() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 13 % 256) as u8).collect();
+        let runs = [
+            SlotRun {
+                data_mon: 0,
+                slot: 200,
+                len: 10,
+            },
+            SlotRun {
+                data_mon: 50,
+                slot: 0,
+                len: 3,
+            },
+        ];
+
+        for flags in [0, PC_FLAG_CHECKSUM] {
+            let mut plain = PC::new();
+            plain.set_flags(flags).unwrap();
+            let mut pc = plain.clone();
+            plain.write_all(&data).unwrap();
+            pc.set_permutation(&runs).unwrap();
+            pc.write_all(&data).unwrap();
+            assert_eq!(pc.verify().unwrap().suspect, vec![]);
+
+            // One table mon then the data mons, the first ten moved to slot 200 on
+            let first = 2;
+            assert_eq!(
+                pc.mon_position_for_bit(0).unwrap(),
+                Some(PC::index_to_position(first + 200))
+            );
+            if flags == 0 {
+                assert_eq!(pc.mons[first + 200], plain.mons[1]);
+                assert_eq!(pc.mons[first], plain.mons[51]);
+                assert_eq!(pc.mons[first + 3], plain.mons[11]);
+            }
+
+            let mut buf = Vec::new();
+            pc.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, data);
+        }
+
+        let mut overlapping = PC::new();
+        let runs = [
+            runs[0],
+            SlotRun {
+                data_mon: 20,
+                ..runs[0]
+            },
+        ];
+        assert!(matches!(
+            overlapping.set_permutation(&runs),
+            Err(MonFsError::BadPermutation)
+        ));
+        assert_eq!(overlapping, PC::new());
+        let mut written = PC::new();
+        written.write_all(&data).unwrap();
+        assert!(matches!(
+            written.set_permutation(&[]),
+            Err(MonFsError::BadPermutation)
+        ));
+    }
 }
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumCount, EnumIter, FromRepr};
+
+use crate::mon_field::{
+    FromGameValueError, FromRepresentation, FromStringInput, Radix, ToGameValueError,
+};
+use crate::mon_name::{BoxMonCharacter, NameAlphabet};
+
+pub const BOX_NAME_LENGTH: usize = 8;
+
+// In game order, picked from the box menu
+#[derive(
+    FromRepr, Debug, Clone, Copy, EnumCount, EnumIter, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[repr(u8)]
+pub enum BoxWallpaper {
+    FOREST,
+    CITY,
+    DESERT,
+    SAVANNA,
+    CRAG,
+    VOLCANO,
+    SNOW,
+    CAVE,
+    BEACH,
+    SEAFLOOR,
+    RIVER,
+    SKY,
+    POLKADOT,
+    POKECENTER,
+    MACHINE,
+    SIMPLE,
+}
+
+impl FromRepresentation for BoxWallpaper {
+    fn from_repr(repr: u8) -> Option<Self> {
+        Self::from_repr(repr)
+    }
+
+    fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl FromStringInput for BoxWallpaper {
+    fn try_from_string(input: &str) -> Option<Self> {
+        Self::iter().find(|wallpaper| format!("{:?}", wallpaper) == input)
+    }
+}
+
+// Box titles are renamed with the same keyboard as mons but are shorter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BoxName {
+    name: [BoxMonCharacter; BOX_NAME_LENGTH],
+}
+
+impl BoxName {
+    pub fn new(name: [BoxMonCharacter; BOX_NAME_LENGTH]) -> Self {
+        BoxName { name }
+    }
+
+    pub fn characters(&self) -> &[BoxMonCharacter; BOX_NAME_LENGTH] {
+        &self.name
+    }
+}
+
+impl FromStringInput for BoxName {
+    fn try_from_string(input: &str) -> Option<Self> {
+        if input.chars().count() != BOX_NAME_LENGTH {
+            return None;
+        }
+
+        let mut name = [BoxMonCharacter::LowerA; BOX_NAME_LENGTH];
+        for (i, character) in input.chars().enumerate() {
+            name[i] = BoxMonCharacter::try_from_string(&character.to_string())?;
+        }
+
+        Some(BoxName { name })
+    }
+}
+
+impl std::fmt::Display for BoxName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for character in &self.name {
+            write!(f, "{}", character)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for BoxName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoxName {
+    fn deserialize<D>(deserializer: D) -> Result<BoxName, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        match BoxName::try_from_string(&name) {
+            Some(name) => Ok(name),
+            None => Err(serde::de::Error::custom("Invalid BoxName")),
+        }
+    }
+}
+
+// What a box is titled and its wallpaper, both free to change from the box menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcBox {
+    pub name: BoxName,
+    pub wallpaper: BoxWallpaper,
+}
+
+impl PcBox {
+    // Different boxes that can be written with names from the alphabet
+    pub fn radix(alphabet: NameAlphabet) -> u128 {
+        (alphabet.characters().len() as u128).pow(BOX_NAME_LENGTH as u32) * BoxWallpaper::radix()
+    }
+
+    // The wallpaper is the least significant digit followed by the name characters
+    pub fn to_value(&self, alphabet: NameAlphabet) -> Result<u128, FromGameValueError> {
+        let characters = alphabet.characters();
+        let base = characters.len() as u128;
+
+        let mut value: u128 = 0;
+        for character in self.name.name.iter().rev() {
+            let digit = match characters.iter().position(|c| c == character) {
+                Some(digit) => digit as u128,
+                None => return Err(FromGameValueError::UnsupportedValue),
+            };
+            value = value * base + digit;
+        }
+
+        Ok(value * BoxWallpaper::radix() + self.wallpaper.to_u8() as u128)
+    }
+
+    pub fn from_value(mut value: u128, alphabet: NameAlphabet) -> Result<PcBox, ToGameValueError> {
+        if value >= Self::radix(alphabet) {
+            return Err(ToGameValueError::BitsValueOutOfRange);
+        }
+
+        let wallpaper = BoxWallpaper::from_repr((value % BoxWallpaper::radix()) as u8);
+        value /= BoxWallpaper::radix();
+
+        let characters = alphabet.characters();
+        let base = characters.len() as u128;
+        let mut name = [BoxMonCharacter::LowerA; BOX_NAME_LENGTH];
+        for character in name.iter_mut() {
+            *character = characters[(value % base) as usize];
+            value /= base;
+        }
+
+        match wallpaper {
+            Some(wallpaper) => Ok(PcBox {
+                name: BoxName { name },
+                wallpaper,
+            }),
+            None => Err(ToGameValueError::BitsValueOutOfRange),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_values_round_trip() {
+        for alphabet in NameAlphabet::iter() {
+            let radix = PcBox::radix(alphabet);
+            for value in [0, 1, 17, radix / 3, radix - 1] {
+                let pc_box = PcBox::from_value(value, alphabet).unwrap();
+                assert_eq!(pc_box.to_value(alphabet).unwrap(), value);
+            }
+            assert!(PcBox::from_value(radix, alphabet).is_err());
+        }
+
+        assert_eq!(PcBox::radix(NameAlphabet::Legacy), 1 << 52);
+    }
+
+    #[test]
+    fn test_from_string() {
+        let name = BoxName::try_from_string("Box 1 ab").unwrap();
+        assert_eq!(name.to_string(), "Box 1 ab");
+        assert_eq!(BoxName::try_from_string("Box 1"), None);
+        assert_eq!(
+            BoxWallpaper::try_from_string("SEAFLOOR"),
+            Some(BoxWallpaper::SEAFLOOR)
+        );
+    }
+}
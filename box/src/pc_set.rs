@@ -0,0 +1,233 @@
+use std::io::{Read, Write};
+
+use crate::{
+    error::MonFsError,
+    fec,
+    file_pc::{empty_pc_with_options, protect, protected_len, FilePc, PcEncodeOptions},
+    pc::{PC, PC_FLAG_FEC, PC_FLAG_SET},
+};
+
+// Start of the data in every PC of a set: set ID (u32 LE), sequence (u8), total (u8),
+// chunk length (u32 LE)
+pub const SET_HEADER_SIZE: usize = 10;
+pub const MAX_SET_SIZE: usize = u8::MAX as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcSetError {
+    // A PC from a set was read on its own
+    PartOfSet { sequence: usize, total: usize },
+    MissingHeader,
+    MixedSets,
+    Missing(usize),
+    Truncated(usize),
+    TooManyPcs(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetHeader {
+    pub set_id: u32,
+    // Starts at 1
+    pub sequence: usize,
+    pub total: usize,
+}
+
+// A FilePc spread over several PCs, one per save, that can be put back together in any order
+#[derive(Debug, Default)]
+pub struct PcSet {
+    pub pcs: Vec<PC>,
+}
+
+impl PcSet {
+    pub fn from_file_pc(file_pc: &FilePc, options: &PcEncodeOptions) -> Result<Self, MonFsError> {
+        let mut pc = empty_pc_with_options(options)?;
+        pc.set_flags(pc.get_flags()? | PC_FLAG_SET)?;
+        let data = file_pc.serialize_with_options(options, &mut pc)?;
+
+        let capacity = pc.remaining_bytes()?;
+        let mut chunk_len = capacity.saturating_sub(SET_HEADER_SIZE);
+        while chunk_len > 0 && protected_len(chunk_len + SET_HEADER_SIZE, options) > capacity {
+            chunk_len -= 1;
+        }
+        if chunk_len == 0 {
+            return Err(MonFsError::CapacityExceeded {
+                have: capacity,
+                need: protected_len(SET_HEADER_SIZE + 1, options),
+            });
+        }
+
+        let chunks: Vec<&[u8]> = data.chunks(chunk_len).collect();
+        let total = chunks.len().max(1);
+        if total > MAX_SET_SIZE {
+            return Err(PcSetError::TooManyPcs(total).into());
+        }
+
+        let set_id = set_id(&data);
+        let mut pcs = vec![];
+        for sequence in 1..=total {
+            let chunk = chunks.get(sequence - 1).copied().unwrap_or_default();
+
+            let mut buf = Vec::with_capacity(SET_HEADER_SIZE + chunk.len());
+            buf.extend_from_slice(&set_id.to_le_bytes());
+            buf.push(sequence as u8);
+            buf.push(total as u8);
+            buf.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            buf.extend_from_slice(chunk);
+
+            let mut set_pc = pc.clone();
+            set_pc.write_all(&protect(buf, options))?;
+            pcs.push(set_pc);
+        }
+
+        Ok(Self { pcs })
+    }
+
+    pub fn into_file_pc(self) -> Result<FilePc, MonFsError> {
+        self.into_file_pc_with_password(None)
+    }
+
+    pub fn into_file_pc_with_password(self, password: Option<&str>) -> Result<FilePc, MonFsError> {
+        let mut parts = vec![];
+        let mut header_pc = None;
+        for pc in self.pcs {
+            header_pc = Some(pc.clone());
+            parts.push(read_part(pc)?);
+        }
+
+        let (first, _) = parts.first().ok_or(PcSetError::Missing(1))?;
+        let (set_id, total) = (first.set_id, first.total);
+        if parts
+            .iter()
+            .any(|(header, _)| header.set_id != set_id || header.total != total)
+        {
+            return Err(PcSetError::MixedSets.into());
+        }
+
+        parts.sort_by_key(|(header, _)| header.sequence);
+        parts.dedup_by_key(|(header, _)| header.sequence);
+        for sequence in 1..=total {
+            if parts.get(sequence - 1).map(|(header, _)| header.sequence) != Some(sequence) {
+                return Err(PcSetError::Missing(sequence).into());
+            }
+        }
+
+        let data: Vec<u8> = parts.into_iter().flat_map(|(_, chunk)| chunk).collect();
+        FilePc::deserialize(&data, &header_pc.unwrap(), password)
+    }
+}
+
+pub fn set_header(pc: &PC) -> Result<Option<SetHeader>, MonFsError> {
+    if pc.get_flags()? & PC_FLAG_SET == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(read_part(pc.clone())?.0))
+}
+
+fn read_part(mut pc: PC) -> Result<(SetHeader, Vec<u8>), MonFsError> {
+    if pc.get_flags()? & PC_FLAG_SET == 0 {
+        return Err(PcSetError::MissingHeader.into());
+    }
+
+    let mut buf = Vec::new();
+    pc.read_to_end(&mut buf)?;
+    if pc.get_flags()? & PC_FLAG_FEC != 0 {
+        buf = fec::decode(&buf)?.0;
+    }
+
+    if buf.len() < SET_HEADER_SIZE {
+        return Err(PcSetError::MissingHeader.into());
+    }
+    let header = SetHeader {
+        set_id: u32::from_le_bytes(buf[0..4].try_into().unwrap()),
+        sequence: buf[4] as usize,
+        total: buf[5] as usize,
+    };
+    let chunk_len = u32::from_le_bytes(buf[6..10].try_into().unwrap()) as usize;
+    if header.sequence == 0 || header.sequence > header.total {
+        return Err(PcSetError::MissingHeader.into());
+    }
+    if buf.len() < SET_HEADER_SIZE + chunk_len {
+        return Err(PcSetError::Truncated(header.sequence).into());
+    }
+
+    Ok((
+        header,
+        buf[SET_HEADER_SIZE..SET_HEADER_SIZE + chunk_len].to_vec(),
+    ))
+}
+
+// FNV-1a so PCs from different sets aren't mixed up
+fn set_id(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::{mon_field::ByteCount, profile::PROFILES};
+
+    fn big_file_pc(rng: &mut ChaCha8Rng) -> FilePc {
+        let random: Vec<u8> = (0..PC::byte_count() * 2).map(|_| rng.gen()).collect();
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("big", random).unwrap();
+        file_pc.add_file_raw("small", vec![7; 20]).unwrap();
+        file_pc
+    }
+
+    #[test]
+    fn reassembles_in_any_order() {
+        let mut rng = ChaCha8Rng::seed_from_u64(13);
+        let file_pc = big_file_pc(&mut rng);
+
+        for profile in PROFILES.iter() {
+            let mut set = PcSet::from_file_pc(&file_pc, &PcEncodeOptions::from(profile)).unwrap();
+            assert!(set.pcs.len() > 1);
+
+            let header = set_header(&set.pcs[1]).unwrap().unwrap();
+            assert_eq!(header.sequence, 2);
+            assert_eq!(header.total, set.pcs.len());
+            assert!(matches!(
+                FilePc::new_from_pc(set.pcs[0].clone()),
+                Err(MonFsError::PcSet(PcSetError::PartOfSet { sequence: 1, .. }))
+            ));
+
+            set.pcs.shuffle(&mut rng);
+            let decoded = set.into_file_pc().unwrap();
+            assert_eq!(decoded.files.len(), 2);
+            assert_eq!(decoded.files[0].data, file_pc.files[0].data);
+            assert_eq!(decoded.files[1].get_data().unwrap(), vec![7; 20]);
+        }
+    }
+
+    #[test]
+    fn reports_missing_and_mixed_pcs() {
+        let mut rng = ChaCha8Rng::seed_from_u64(14);
+        let options = PcEncodeOptions::default();
+
+        let mut set = PcSet::from_file_pc(&big_file_pc(&mut rng), &options).unwrap();
+        set.pcs.remove(1);
+        assert!(matches!(
+            set.into_file_pc(),
+            Err(MonFsError::PcSet(PcSetError::Missing(2)))
+        ));
+
+        let mut set = PcSet::from_file_pc(&big_file_pc(&mut rng), &options).unwrap();
+        let other = PcSet::from_file_pc(&big_file_pc(&mut rng), &options).unwrap();
+        set.pcs[0] = other.pcs[0].clone();
+        assert!(matches!(
+            set.into_file_pc(),
+            Err(MonFsError::PcSet(PcSetError::MixedSets))
+        ));
+
+        let plain = FilePc::new().as_pc().unwrap();
+        assert!(matches!(
+            PcSet { pcs: vec![plain] }.into_file_pc(),
+            Err(MonFsError::PcSet(PcSetError::MissingHeader))
+        ));
+    }
+}
@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    box_mon::BoxMon,
+    mon_held_item::BoxMonHeldItem,
+    mon_markings::BoxMonMarkings,
+    pc::{MonPosition, PC, PC_BOX_SIZE},
+    pc_box::PcBox,
+};
+
+// One step to turn the PC in game into the one a guide asks for. Moving a mon onto a slot
+// that already holds one swaps them, like the move mode in the PC menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlanAction {
+    Release {
+        position: MonPosition,
+        mon: BoxMon,
+    },
+    Move {
+        from: MonPosition,
+        to: MonPosition,
+        mon: BoxMon,
+    },
+    Catch {
+        position: MonPosition,
+        mon: BoxMon,
+    },
+    SwapItem {
+        position: MonPosition,
+        from: BoxMonHeldItem,
+        to: BoxMonHeldItem,
+    },
+    SetMarkings {
+        position: MonPosition,
+        markings: Option<BoxMonMarkings>,
+    },
+    SetBox {
+        box_index: usize,
+        pc_box: Option<PcBox>,
+    },
+}
+
+impl std::fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let slot = |position: &MonPosition| {
+            format!("box {} slot {}", position.box_index + 1, position.slot + 1)
+        };
+        let mon = |mon: &BoxMon| format!("{:?} {:?} {}", mon.gender, mon.species, mon.name);
+        let item = |item: &BoxMonHeldItem| match item {
+            BoxMonHeldItem::Empty => "nothing".to_string(),
+            item => item.to_string(),
+        };
+
+        match self {
+            PlanAction::Release { position, mon: m } => {
+                write!(f, "Release {} from {}", mon(m), slot(position))
+            }
+            PlanAction::Move { from, to, mon: m } => {
+                write!(f, "Move {} from {} to {}", mon(m), slot(from), slot(to))
+            }
+            PlanAction::Catch { position, mon: m } => write!(
+                f,
+                "Catch {} holding {} into {}",
+                mon(m),
+                item(&m.held_item),
+                slot(position)
+            ),
+            PlanAction::SwapItem { position, from, to } => write!(
+                f,
+                "Swap the {} held in {} for {}",
+                item(from),
+                slot(position),
+                item(to)
+            ),
+            PlanAction::SetMarkings { position, markings } => match markings {
+                Some(markings) => write!(f, "Mark {} with {}", slot(position), markings),
+                None => write!(f, "Clear the markings on {}", slot(position)),
+            },
+            PlanAction::SetBox { box_index, pc_box } => match pc_box {
+                Some(pc_box) => write!(
+                    f,
+                    "Rename box {} to {} with the {:?} wallpaper",
+                    box_index + 1,
+                    pc_box.name,
+                    pc_box.wallpaper
+                ),
+                None => write!(f, "Box {} title and wallpaper are free", box_index + 1),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    pub actions: Vec<PlanAction>,
+}
+
+impl MigrationPlan {
+    // Releases come first to free up slots, then moves, catches and finally the held items,
+    // markings and boxes that can be changed in place
+    pub fn new(from: &PC, to: &PC) -> Self {
+        let slots = from.mons.len().max(to.mons.len());
+        let mon_at = |pc: &PC, index: usize| pc.mons.get(index).copied().flatten();
+
+        // Mons that can stay where they are, even if their item or markings change
+        let mut kept = vec![false; slots];
+        for (index, keep) in kept.iter_mut().enumerate() {
+            if let (Some(have), Some(want)) = (mon_at(from, index), mon_at(to, index)) {
+                *keep = same_mon(&have, &want);
+            }
+        }
+
+        // Everything else is either moved to a slot that wants an identical mon or released
+        let mut spare: Vec<usize> = (0..slots)
+            .filter(|index| !kept[*index] && mon_at(from, *index).is_some())
+            .collect();
+        let mut moves = vec![];
+        let mut catches = vec![];
+        for index in (0..slots).filter(|index| !kept[*index]) {
+            let want = match mon_at(to, index) {
+                Some(want) => want,
+                None => continue,
+            };
+            let found = spare
+                .iter()
+                .position(|spare| same_mon(&mon_at(from, *spare).unwrap(), &want));
+            match found {
+                Some(i) => moves.push((spare.remove(i), index)),
+                None => catches.push(index),
+            }
+        }
+
+        let mut actions = vec![];
+        let mut current = from.mons.clone();
+        current.resize(slots, None);
+        for index in spare {
+            actions.push(PlanAction::Release {
+                position: position(index),
+                mon: current[index].take().unwrap(),
+            });
+        }
+
+        // Moves onto empty slots go first so as few mons as possible are swapped
+        while !moves.is_empty() {
+            let next = moves
+                .iter()
+                .position(|(_, to)| current[*to].is_none())
+                .unwrap_or(0);
+            let (source, target) = moves.remove(next);
+
+            let mon = current[source].take().unwrap();
+            if let Some(displaced) = current[target].replace(mon) {
+                current[source] = Some(displaced);
+                for (pending, _) in moves.iter_mut().filter(|(pending, _)| *pending == target) {
+                    *pending = source;
+                }
+            }
+            actions.push(PlanAction::Move {
+                from: position(source),
+                to: position(target),
+                mon,
+            });
+        }
+
+        for index in catches {
+            let mon = mon_at(to, index).unwrap();
+            current[index] = Some(mon);
+            actions.push(PlanAction::Catch {
+                position: position(index),
+                mon,
+            });
+        }
+
+        for (index, have) in current.iter().enumerate() {
+            let (have, want) = match (have, mon_at(to, index)) {
+                (Some(have), Some(want)) => (have, want),
+                _ => continue,
+            };
+            if have.held_item != want.held_item {
+                actions.push(PlanAction::SwapItem {
+                    position: position(index),
+                    from: have.held_item,
+                    to: want.held_item,
+                });
+            }
+            if have.markings.unwrap_or_default() != want.markings.unwrap_or_default() {
+                actions.push(PlanAction::SetMarkings {
+                    position: position(index),
+                    markings: want.markings,
+                });
+            }
+        }
+
+        for box_index in from.diff(to).boxes {
+            actions.push(PlanAction::SetBox {
+                box_index,
+                pc_box: to.boxes.get(box_index).copied(),
+            });
+        }
+
+        Self { actions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+// Whether a mon can be used as is, held items and markings can be changed without a recatch
+fn same_mon(have: &BoxMon, want: &BoxMon) -> bool {
+    have.species == want.species
+        && have.gender == want.gender
+        && have.name == want.name
+        && (want.captured_ball.is_none() || have.captured_ball == want.captured_ball)
+        && (want.nature.is_none() || have.nature == want.nature)
+}
+
+fn position(index: usize) -> MonPosition {
+    MonPosition {
+        box_index: index / PC_BOX_SIZE,
+        slot: index % PC_BOX_SIZE,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+    use crate::{file_pc::FilePc, mon_species::BoxMonSpecies};
+
+    // Plays the plan out on a copy of from
+    fn apply(from: &PC, plan: &MigrationPlan) -> PC {
+        let mut pc = from.clone();
+        let index = |position: &MonPosition| position.box_index * PC_BOX_SIZE + position.slot;
+        for action in &plan.actions {
+            match action {
+                PlanAction::Release { position, .. } => pc.mons[index(position)] = None,
+                PlanAction::Move { from, to, .. } => pc.mons.swap(index(from), index(to)),
+                PlanAction::Catch { position, mon } => pc.mons[index(position)] = Some(*mon),
+                PlanAction::SwapItem { position, to, .. } => {
+                    pc.mons[index(position)].as_mut().unwrap().held_item = *to
+                }
+                PlanAction::SetMarkings { position, markings } => {
+                    pc.mons[index(position)].as_mut().unwrap().markings = *markings
+                }
+                PlanAction::SetBox { box_index, pc_box } => match pc_box {
+                    Some(pc_box) => pc.boxes[*box_index] = *pc_box,
+                    None => pc.boxes.clear(),
+                },
+            }
+        }
+        pc
+    }
+
+    #[test]
+    fn plan_turns_one_pc_into_another() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a", vec![1, 2, 3, 4, 5]).unwrap();
+        let from = file_pc.as_pc().unwrap();
+        file_pc.add_file_raw("b", vec![9; 200]).unwrap();
+        let to = file_pc.as_pc().unwrap();
+
+        let plan = MigrationPlan::new(&from, &to);
+        assert_eq!(apply(&from, &plan).mons, to.mons);
+        assert!(MigrationPlan::new(&to, &to).is_empty());
+    }
+
+    #[test]
+    fn moved_mons_are_not_recaught() {
+        let mut from = PC::new();
+        from.write_all(&[7; 40]).unwrap();
+
+        // Shift every mon along a slot and change what the first one holds
+        let mut to = PC::new();
+        let mut fresh = from.mons[1].unwrap();
+        fresh.species = match fresh.species {
+            BoxMonSpecies::TAILLOW => BoxMonSpecies::POOCHYENA,
+            _ => BoxMonSpecies::TAILLOW,
+        };
+        to.mons[1] = Some(fresh);
+        for index in 1..6 {
+            to.mons[index + 1] = from.mons[index];
+        }
+        to.mons[2].as_mut().unwrap().held_item = BoxMonHeldItem::Potion;
+
+        let plan = MigrationPlan::new(&from, &to);
+        assert_eq!(apply(&from, &plan).mons, to.mons);
+        let count = |f: fn(&PlanAction) -> bool| plan.actions.iter().filter(|a| f(a)).count();
+        assert_eq!(count(|a| matches!(a, PlanAction::Catch { .. })), 1);
+        assert_eq!(count(|a| matches!(a, PlanAction::SwapItem { .. })), 1);
+        assert_eq!(count(|a| matches!(a, PlanAction::Move { .. })), 5);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::box_mon::{MonLayout, MonPacking};
+use crate::mon_name::NameAlphabet;
+
+// Bumped once per released format, not for every change on the way to it. The header only
+// has 4 bits for it.
+// Version 0 is every PC written before the header carried a version, files in bincode.
+// Version 1 added profiles and the layout fields to the header, the flags and the file
+// container, see container.
+pub const FORMAT_VERSION: u8 = 1;
+
+// Profile ID for PCs whose layout is spelled out field by field in the header
+pub const CUSTOM_PROFILE_ID: u8 = 0;
+
+// A named set of encoding choices. IDs are written into PCs so they must never be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodingProfile {
+    pub id: u8,
+    pub name: &'static str,
+    pub layout: MonLayout,
+    pub mon_checksums: bool,
+    pub parity_mons: usize,
+    pub compress_files: bool,
+    pub box_data: bool,
+}
+
+pub const PROFILES: [EncodingProfile; 5] = [
+    // What mon-fs wrote before profiles existed
+    EncodingProfile {
+        id: 1,
+        name: "legacy",
+        layout: MonLayout {
+            alphabet: NameAlphabet::Legacy,
+            packing: MonPacking::Bits,
+            captured_ball: false,
+            markings: false,
+            nature: false,
+        },
+        mon_checksums: false,
+        parity_mons: 0,
+        compress_files: true,
+        box_data: false,
+    },
+    // For decoding from screenshots where a few misreads are expected
+    EncodingProfile {
+        id: 2,
+        name: "robust",
+        layout: MonLayout {
+            alphabet: NameAlphabet::Legacy,
+            packing: MonPacking::Bits,
+            captured_ball: false,
+            markings: false,
+            nature: false,
+        },
+        mon_checksums: true,
+        parity_mons: 40,
+        compress_files: true,
+        box_data: false,
+    },
+    // More capacity while keeping names easy to read
+    EncodingProfile {
+        id: 3,
+        name: "dense",
+        layout: MonLayout {
+            alphabet: NameAlphabet::FullUnambiguous,
+            packing: MonPacking::MixedRadix(30),
+            captured_ball: false,
+            markings: false,
+            nature: false,
+        },
+        mon_checksums: false,
+        parity_mons: 0,
+        compress_files: true,
+        box_data: false,
+    },
+    // Everything, only practical when the PC is read back from a save file
+    EncodingProfile {
+        id: 4,
+        name: "save",
+        layout: MonLayout {
+            alphabet: NameAlphabet::Full,
+            packing: MonPacking::MixedRadix(418),
+            captured_ball: true,
+            markings: false,
+            nature: false,
+        },
+        mon_checksums: false,
+        parity_mons: 0,
+        compress_files: true,
+        box_data: false,
+    },
+    // The legacy layout plus markings, which cost nothing to set in the PC
+    EncodingProfile {
+        id: 5,
+        name: "marked",
+        layout: MonLayout {
+            alphabet: NameAlphabet::Legacy,
+            packing: MonPacking::Bits,
+            captured_ball: false,
+            markings: true,
+            nature: false,
+        },
+        mon_checksums: false,
+        parity_mons: 0,
+        compress_files: true,
+        box_data: false,
+    },
+];
+
+pub fn profile_by_id(id: u8) -> Option<&'static EncodingProfile> {
+    PROFILES.iter().find(|profile| profile.id == id)
+}
+
+pub fn profile_by_name(name: &str) -> Option<&'static EncodingProfile> {
+    PROFILES.iter().find(|profile| profile.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_ids_are_unique() {
+        for (i, profile) in PROFILES.iter().enumerate() {
+            assert_ne!(profile.id, CUSTOM_PROFILE_ID);
+            assert!(PROFILES[i + 1..].iter().all(|other| other.id != profile.id));
+            assert!(PROFILES[i + 1..]
+                .iter()
+                .all(|other| other.name != profile.name));
+            assert_eq!(profile_by_id(profile.id), Some(profile));
+            assert_eq!(profile_by_name(profile.name), Some(profile));
+        }
+
+        assert_eq!(profile_by_id(CUSTOM_PROFILE_ID), None);
+        assert_eq!(profile_by_name("missing"), None);
+    }
+}
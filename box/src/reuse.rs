@@ -0,0 +1,180 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::{
+    box_mon::MonPacking,
+    error::MonFsError,
+    pc::{SlotRun, MAX_PERMUTATION_MONS, NUM_OF_DATA_MONS, PC},
+};
+
+// Rounds of resizing the permutation table before giving up, the table taking up more mons
+// moves the data mons which changes how many swaps are needed
+const MAX_ROUNDS: usize = 8;
+
+// The same data as pc with the data mons moved around so as many as possible land on a mon
+// the player already has in owned. Only PCs with bits packing can be moved around, PCs where
+// moving mons doesn't help are returned as is
+pub fn reuse_owned_mons(pc: &PC, owned: &PC) -> Result<PC, MonFsError> {
+    let layout = pc.get_layout()?;
+    if let MonPacking::MixedRadix(run_length) = layout.packing {
+        return Err(MonFsError::MixedRadixReuse(run_length));
+    }
+
+    // Owned mons by the payload they would hold with this PC's layout
+    let payload_bits = pc.payload_bit_count()?;
+    let mut owned_slots: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    for slot in 1..=NUM_OF_DATA_MONS {
+        let mon = match owned.mons.get(slot).copied().flatten() {
+            Some(mon) => mon,
+            None => continue,
+        };
+        if let Ok(bits) = layout.mon_to_bits(&mon) {
+            let payload = bits.chunk(0, payload_bits).to_raw();
+            owned_slots.entry(payload).or_default().push(slot);
+        }
+    }
+
+    let payloads = pc.data_payloads()?;
+    let reusable = |slot: usize, data_mon: usize| -> Result<bool, MonFsError> {
+        let want = pc.mon_from_payload(slot, payloads[data_mon].clone())?;
+        Ok(owned.mons.get(slot).copied().flatten() == Some(want))
+    };
+
+    let mut in_place = 0;
+    for data_mon in 0..payloads.len() {
+        if reusable(data_mon + 1, data_mon)? {
+            in_place += 1;
+        }
+    }
+
+    let mut table_mons = 0;
+    for _ in 0..MAX_ROUNDS {
+        if table_mons > MAX_PERMUTATION_MONS || payloads.len() + table_mons > NUM_OF_DATA_MONS {
+            break;
+        }
+
+        // Slots counted from the first one after the table, a mon carrying on the run before
+        // it is preferred so the table stays small
+        let first = table_mons + 1;
+        let mut taken = vec![false; NUM_OF_DATA_MONS - table_mons];
+        let mut runs: Vec<SlotRun> = vec![];
+        let mut reused = 0;
+        for (data_mon, payload) in payloads.iter().enumerate() {
+            let next = runs
+                .last()
+                .filter(|run| run.data_mon + run.len == data_mon)
+                .map(|run| run.slot + run.len);
+            let owned = owned_slots.get(&payload.to_raw()).into_iter().flatten();
+            let candidates = next
+                .into_iter()
+                .chain([data_mon])
+                .chain(owned.filter_map(|slot| slot.checked_sub(first)));
+
+            let mut target = None;
+            for slot in candidates {
+                if slot < taken.len() && !taken[slot] && reusable(first + slot, data_mon)? {
+                    target = Some(slot);
+                    break;
+                }
+            }
+            let slot = match target {
+                Some(slot) => slot,
+                None => continue,
+            };
+            taken[slot] = true;
+            reused += 1;
+
+            match runs.last_mut() {
+                Some(run) if next == Some(slot) => run.len += 1,
+                _ => runs.push(SlotRun {
+                    data_mon,
+                    slot,
+                    len: 1,
+                }),
+            }
+        }
+
+        let needed = pc.permutation_table_mons(runs.len())?;
+        if needed != table_mons {
+            table_mons = needed;
+            continue;
+        }
+        if reused <= in_place {
+            break;
+        }
+
+        let mut permuted = pc.cleared()?;
+        permuted.set_permutation(&runs)?;
+        permuted.write_all(&pc.clone().data()?)?;
+        return Ok(permuted);
+    }
+
+    Ok(pc.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::{box_mon::MonLayout, pc::PC_FLAG_CHECKSUM};
+
+    #[test]
+    fn shifted_data_reuses_the_mons_already_caught() {
+        let data: Vec<u8> = (0..2000).map(|i| (i * 7 % 251) as u8).collect();
+        for flags in [0, PC_FLAG_CHECKSUM] {
+            let mut owned = PC::new();
+            owned.set_flags(flags).unwrap();
+            owned.write_all(&data).unwrap();
+
+            // Two mons worth of new data at the start pushes every old mon along two slots
+            let payload_bytes = owned.payload_bit_count().unwrap() * 2 / 8;
+            let mut pc = PC::new();
+            pc.set_flags(flags).unwrap();
+            pc.write_all(&vec![0xEE; payload_bytes]).unwrap();
+            pc.write_all(&data).unwrap();
+
+            let same = |pc: &PC| {
+                pc.mons
+                    .iter()
+                    .zip(&owned.mons)
+                    .skip(1)
+                    .filter(|(a, b)| a.is_some() && a == b)
+                    .count()
+            };
+            let permuted = reuse_owned_mons(&pc, &owned).unwrap();
+            assert!(same(&pc) < 20);
+            assert!(same(&permuted) > 150);
+            assert_eq!(permuted.verify().unwrap().suspect, vec![]);
+
+            let mut buf = vec![];
+            permuted.clone().read_to_end(&mut buf).unwrap();
+            assert_eq!(buf[payload_bytes..], data);
+        }
+    }
+
+    #[test]
+    fn unrelated_data_is_left_alone() {
+        let mut owned = PC::new();
+        owned.write_all(&[1; 300]).unwrap();
+        let mut pc = PC::new();
+        pc.write_all(&[2; 300]).unwrap();
+
+        assert_eq!(reuse_owned_mons(&pc, &owned).unwrap(), pc);
+    }
+
+    #[test]
+    fn mixed_radix_pcs_say_they_cant_be_moved() {
+        let mut pc = PC::new();
+        pc.set_layout(&MonLayout {
+            packing: MonPacking::MixedRadix(30),
+            ..Default::default()
+        })
+        .unwrap();
+        pc.write_all(&[2; 300]).unwrap();
+
+        assert!(matches!(
+            reuse_owned_mons(&pc, &PC::new()),
+            Err(MonFsError::MixedRadixReuse(30))
+        ));
+    }
+}
@@ -0,0 +1,918 @@
+use crate::box_mon::{BoxMon, MonLayout};
+use crate::mon_captured_ball::BoxMonCapturedBall;
+use crate::mon_field::FromRepresentation;
+use crate::mon_gender::BoxMonGender;
+use crate::mon_held_item::BoxMonHeldItem;
+use crate::mon_markings::BoxMonMarkings;
+use crate::mon_name::{BoxMonCharacter, BoxMonName};
+use crate::mon_nature::BoxMonNature;
+use crate::mon_species::BoxMonSpecies;
+use crate::pc::{NUM_OF_MONS, NUM_PC_BOXES, PC, PC_BOX_SIZE, PC_FLAG_BOX_DATA};
+use crate::pc_box::{BoxName, BoxWallpaper, PcBox, BOX_NAME_LENGTH};
+
+// Layout of a 128 KiB Emerald save.
+// Two rotating save slots of 14 sections each, every section is 4 KiB with a footer.
+pub const SAVE_SIZE: usize = 0x20000;
+const SECTION_SIZE: usize = 0x1000;
+const SECTIONS_PER_SLOT: usize = 14;
+const SLOT_SIZE: usize = SECTION_SIZE * SECTIONS_PER_SLOT;
+const NUM_SLOTS: usize = 2;
+
+const SECTION_ID_OFFSET: usize = 0xFF4;
+const SECTION_CHECKSUM_OFFSET: usize = 0xFF6;
+const SECTION_SIGNATURE_OFFSET: usize = 0xFF8;
+const SECTION_SAVE_INDEX_OFFSET: usize = 0xFFC;
+const SECTION_SIGNATURE: u32 = 0x08012025;
+
+// How many bytes of each section are covered by its checksum, indexed by section ID.
+const SECTION_DATA_SIZES: [usize; SECTIONS_PER_SLOT] = [
+    3884, 3968, 3968, 3968, 3848, 3968, 3968, 3968, 3968, 3968, 3968, 3968, 3968, 2000,
+];
+
+// Trainer info lives in section 0 (SaveBlock2)
+const TRAINER_INFO_SECTION: usize = 0;
+const TRAINER_NAME_OFFSET: usize = 0x00;
+const TRAINER_NAME_LENGTH: usize = 7;
+const TRAINER_ID_OFFSET: usize = 0x0A;
+
+// The PC buffer is split across sections 5 to 13
+const PC_BUFFER_FIRST_SECTION: usize = 5;
+const PC_MONS_OFFSET: usize = 4;
+const PC_BOX_NAMES_OFFSET: usize = PC_MONS_OFFSET + NUM_OF_MONS * BOX_MON_SIZE;
+// Box names have room for a terminator after the 8 characters
+const PC_BOX_NAME_SIZE: usize = BOX_NAME_LENGTH + 1;
+const PC_BOX_WALLPAPERS_OFFSET: usize = PC_BOX_NAMES_OFFSET + NUM_PC_BOXES * PC_BOX_NAME_SIZE;
+
+const BOX_MON_SIZE: usize = 80;
+const BOX_MON_NICKNAME_OFFSET: usize = 8;
+const BOX_MON_NICKNAME_LENGTH: usize = 10;
+const BOX_MON_LANGUAGE_OFFSET: usize = 18;
+const BOX_MON_FLAGS_OFFSET: usize = 19;
+const BOX_MON_OT_NAME_OFFSET: usize = 20;
+const BOX_MON_MARKINGS_OFFSET: usize = 27;
+const BOX_MON_CHECKSUM_OFFSET: usize = 28;
+const BOX_MON_SECURE_OFFSET: usize = 32;
+const BOX_MON_SECURE_SIZE: usize = 48;
+const SUBSTRUCT_SIZE: usize = 12;
+
+const LANGUAGE_ENGLISH: u8 = 2;
+const FLAG_HAS_SPECIES: u8 = 0x02;
+const STRING_TERMINATOR: u8 = 0xFF;
+
+// Every species mon-fs uses has a 50/50 gender ratio
+const GENDER_RATIO: u8 = 127;
+const NATURE_COUNT: u32 = 25;
+const MET_LOCATION_ROUTE_116: u8 = 0x1F;
+const MET_LEVEL: u16 = 5;
+const ORIGIN_GAME_EMERALD: u16 = 3;
+const POKE_BALL: u16 = 4;
+const ORIGINS_BALL_SHIFT: u16 = 11;
+const ORIGINS_BALL_MASK: u16 = 0xF << ORIGINS_BALL_SHIFT;
+const BASE_FRIENDSHIP: u8 = 70;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Substruct {
+    Growth,
+    Attacks,
+    Evs,
+    Misc,
+}
+
+// Substructure order for personality % 24
+const SUBSTRUCT_ORDERS: [[Substruct; 4]; 24] = {
+    use Substruct::{Attacks as A, Evs as E, Growth as G, Misc as M};
+    [
+        [G, A, E, M],
+        [G, A, M, E],
+        [G, E, A, M],
+        [G, E, M, A],
+        [G, M, A, E],
+        [G, M, E, A],
+        [A, G, E, M],
+        [A, G, M, E],
+        [A, E, G, M],
+        [A, E, M, G],
+        [A, M, G, E],
+        [A, M, E, G],
+        [E, G, A, M],
+        [E, G, M, A],
+        [E, A, G, M],
+        [E, A, M, G],
+        [E, M, G, A],
+        [E, M, A, G],
+        [M, G, A, E],
+        [M, G, E, A],
+        [M, A, G, E],
+        [M, A, E, G],
+        [M, E, G, A],
+        [M, E, A, G],
+    ]
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveError {
+    BadSize(usize),
+    NoValidSlot,
+    BadMonChecksum {
+        box_index: usize,
+        slot: usize,
+    },
+    UnsupportedSpecies {
+        box_index: usize,
+        slot: usize,
+        species: u16,
+    },
+    UnsupportedItem {
+        box_index: usize,
+        slot: usize,
+        item: u16,
+    },
+    UnsupportedNickname {
+        box_index: usize,
+        slot: usize,
+    },
+    UnsupportedBoxName {
+        box_index: usize,
+    },
+    UnsupportedWallpaper {
+        box_index: usize,
+        wallpaper: u8,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct EmeraldSave {
+    raw: Vec<u8>,
+    // Offset into raw of each section in the active slot, indexed by section ID
+    section_offsets: [usize; SECTIONS_PER_SLOT],
+}
+
+impl EmeraldSave {
+    pub fn new(raw: Vec<u8>) -> Result<Self, SaveError> {
+        if raw.len() < SLOT_SIZE * NUM_SLOTS {
+            return Err(SaveError::BadSize(raw.len()));
+        }
+
+        let mut best: Option<(u32, [usize; SECTIONS_PER_SLOT])> = None;
+        for slot in 0..NUM_SLOTS {
+            if let Some((save_index, offsets)) = read_slot(&raw, slot * SLOT_SIZE) {
+                if best.is_none_or(|(best_index, _)| save_index > best_index) {
+                    best = Some((save_index, offsets));
+                }
+            }
+        }
+
+        match best {
+            Some((_, section_offsets)) => Ok(EmeraldSave {
+                raw,
+                section_offsets,
+            }),
+            None => Err(SaveError::NoValidSlot),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.raw
+    }
+
+    pub fn trainer_id(&self) -> u32 {
+        let offset = self.section_offsets[TRAINER_INFO_SECTION] + TRAINER_ID_OFFSET;
+        read_u32(&self.raw, offset)
+    }
+
+    fn trainer_name(&self) -> [u8; TRAINER_NAME_LENGTH] {
+        let offset = self.section_offsets[TRAINER_INFO_SECTION] + TRAINER_NAME_OFFSET;
+        let mut name = [0; TRAINER_NAME_LENGTH];
+        name.copy_from_slice(&self.raw[offset..offset + TRAINER_NAME_LENGTH]);
+        name
+    }
+
+    fn pc_buffer(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let sections = self.section_offsets.iter().zip(SECTION_DATA_SIZES.iter());
+        for (offset, size) in sections.skip(PC_BUFFER_FIRST_SECTION) {
+            buffer.extend_from_slice(&self.raw[*offset..*offset + *size]);
+        }
+        buffer
+    }
+
+    fn set_pc_buffer(&mut self, buffer: &[u8]) {
+        let mut read = 0;
+        let sections = self.section_offsets.iter().zip(SECTION_DATA_SIZES.iter());
+        for (&offset, &size) in sections.skip(PC_BUFFER_FIRST_SECTION) {
+            self.raw[offset..offset + size].copy_from_slice(&buffer[read..read + size]);
+            read += size;
+
+            let checksum = section_checksum(&self.raw[offset..offset + size]);
+            write_u16(&mut self.raw, offset + SECTION_CHECKSUM_OFFSET, checksum);
+        }
+    }
+
+    pub fn to_pc(&self) -> Result<PC, SaveError> {
+        let buffer = self.pc_buffer();
+
+        let mut pc = PC::new();
+        for index in 0..NUM_OF_MONS {
+            let box_index = index / PC_BOX_SIZE;
+            let slot = index % PC_BOX_SIZE;
+
+            let offset = PC_MONS_OFFSET + index * BOX_MON_SIZE;
+            let raw_mon = &buffer[offset..offset + BOX_MON_SIZE];
+            if let Some(mon) = decode_box_mon(raw_mon, box_index, slot)? {
+                pc.set_mon(box_index, slot, mon);
+            }
+        }
+
+        // Optional fields are only part of the data when the header says so, never in the
+        // header itself
+        let layout = pc.get_layout().unwrap_or_default();
+        for (index, mon) in pc.mons.iter_mut().enumerate() {
+            if let Some(mon) = mon {
+                match index {
+                    0 => MonLayout::default().strip_unused(mon),
+                    _ => layout.strip_unused(mon),
+                }
+            }
+        }
+
+        if pc.get_flags().unwrap_or(0) & PC_FLAG_BOX_DATA != 0 {
+            for box_index in 0..NUM_PC_BOXES {
+                pc.boxes.push(decode_pc_box(&buffer, box_index)?);
+            }
+        }
+
+        Ok(pc)
+    }
+
+    pub fn write_pc(&mut self, pc: &PC) -> Result<(), SaveError> {
+        let mut buffer = self.pc_buffer();
+        let ot_id = self.trainer_id();
+        let ot_name = self.trainer_name();
+
+        for index in 0..NUM_OF_MONS {
+            let offset = PC_MONS_OFFSET + index * BOX_MON_SIZE;
+            let raw_mon = &mut buffer[offset..offset + BOX_MON_SIZE];
+
+            match pc.mons.get(index).copied().flatten() {
+                Some(mon) => encode_box_mon(raw_mon, &mon, index, ot_id, &ot_name),
+                None => raw_mon.fill(0),
+            }
+        }
+
+        for (box_index, pc_box) in pc.boxes.iter().enumerate() {
+            encode_pc_box(&mut buffer, box_index, pc_box);
+        }
+
+        self.set_pc_buffer(&buffer);
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&EmeraldSave> for PC {
+    type Error = SaveError;
+
+    fn try_from(value: &EmeraldSave) -> Result<Self, Self::Error> {
+        value.to_pc()
+    }
+}
+
+fn read_slot(raw: &[u8], slot_offset: usize) -> Option<(u32, [usize; SECTIONS_PER_SLOT])> {
+    let mut offsets = [None; SECTIONS_PER_SLOT];
+    let mut save_index = None;
+
+    for position in 0..SECTIONS_PER_SLOT {
+        let offset = slot_offset + position * SECTION_SIZE;
+        if read_u32(raw, offset + SECTION_SIGNATURE_OFFSET) != SECTION_SIGNATURE {
+            return None;
+        }
+
+        let id = read_u16(raw, offset + SECTION_ID_OFFSET) as usize;
+        if id >= SECTIONS_PER_SLOT || offsets[id].is_some() {
+            return None;
+        }
+
+        let checksum = section_checksum(&raw[offset..offset + SECTION_DATA_SIZES[id]]);
+        if checksum != read_u16(raw, offset + SECTION_CHECKSUM_OFFSET) {
+            return None;
+        }
+
+        let index = read_u32(raw, offset + SECTION_SAVE_INDEX_OFFSET);
+        if *save_index.get_or_insert(index) != index {
+            return None;
+        }
+
+        offsets[id] = Some(offset);
+    }
+
+    let mut result = [0; SECTIONS_PER_SLOT];
+    for (id, offset) in offsets.iter().enumerate() {
+        result[id] = (*offset)?;
+    }
+
+    Some((save_index?, result))
+}
+
+fn section_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+    for word in data.chunks_exact(4) {
+        sum = sum.wrapping_add(u32::from_le_bytes([word[0], word[1], word[2], word[3]]));
+    }
+    ((sum >> 16) as u16).wrapping_add(sum as u16)
+}
+
+fn substruct_offset(personality: u32, substruct: Substruct) -> usize {
+    let order = &SUBSTRUCT_ORDERS[(personality % 24) as usize];
+    let position = order.iter().position(|s| *s == substruct).unwrap();
+    position * SUBSTRUCT_SIZE
+}
+
+fn crypt_secure(secure: &mut [u8], key: u32) {
+    for word in secure.chunks_exact_mut(4) {
+        let value = u32::from_le_bytes([word[0], word[1], word[2], word[3]]) ^ key;
+        word.copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn secure_checksum(secure: &[u8]) -> u16 {
+    secure.chunks_exact(2).fold(0u16, |sum, half| {
+        sum.wrapping_add(u16::from_le_bytes([half[0], half[1]]))
+    })
+}
+
+fn decode_box_mon(raw: &[u8], box_index: usize, slot: usize) -> Result<Option<BoxMon>, SaveError> {
+    let personality = read_u32(raw, 0);
+    let ot_id = read_u32(raw, 4);
+
+    let mut secure = [0; BOX_MON_SECURE_SIZE];
+    secure
+        .copy_from_slice(&raw[BOX_MON_SECURE_OFFSET..BOX_MON_SECURE_OFFSET + BOX_MON_SECURE_SIZE]);
+    crypt_secure(&mut secure, personality ^ ot_id);
+
+    let growth = substruct_offset(personality, Substruct::Growth);
+    let species_id = read_u16(&secure, growth);
+    if species_id == 0 {
+        return Ok(None);
+    }
+
+    if secure_checksum(&secure) != read_u16(raw, BOX_MON_CHECKSUM_OFFSET) {
+        return Err(SaveError::BadMonChecksum { box_index, slot });
+    }
+
+    let species = match species_from_game_id(species_id) {
+        Some(species) => species,
+        None => {
+            return Err(SaveError::UnsupportedSpecies {
+                box_index,
+                slot,
+                species: species_id,
+            })
+        }
+    };
+
+    let item_id = read_u16(&secure, growth + 2);
+    let held_item = match held_item_from_game_id(item_id) {
+        Some(held_item) => held_item,
+        None => {
+            return Err(SaveError::UnsupportedItem {
+                box_index,
+                slot,
+                item: item_id,
+            })
+        }
+    };
+
+    let misc = substruct_offset(personality, Substruct::Misc);
+    let ball_id = (read_u16(&secure, misc + 2) & ORIGINS_BALL_MASK) >> ORIGINS_BALL_SHIFT;
+
+    let mut name = [BoxMonCharacter::default(); BOX_MON_NICKNAME_LENGTH];
+    for (i, character) in name.iter_mut().enumerate() {
+        *character = match character_from_game_value(raw[BOX_MON_NICKNAME_OFFSET + i]) {
+            Some(character) => character,
+            None => return Err(SaveError::UnsupportedNickname { box_index, slot }),
+        };
+    }
+
+    Ok(Some(BoxMon {
+        species,
+        gender: gender_from_personality(personality),
+        name: BoxMonName::new(name),
+        held_item,
+        captured_ball: captured_ball_from_game_id(ball_id),
+        markings: BoxMonMarkings::from_repr(raw[BOX_MON_MARKINGS_OFFSET] & 0xF),
+        nature: BoxMonNature::from_repr((personality % NATURE_COUNT) as u8),
+    }))
+}
+
+fn encode_box_mon(
+    raw: &mut [u8],
+    mon: &BoxMon,
+    index: usize,
+    ot_id: u32,
+    ot_name: &[u8; TRAINER_NAME_LENGTH],
+) {
+    // Keep the mon already in the slot if it is the same species, gender and nature so only
+    // the nickname, held item and markings change. Otherwise a fresh mon is generated.
+    let existing = decode_box_mon(raw, index / PC_BOX_SIZE, index % PC_BOX_SIZE)
+        .ok()
+        .flatten();
+    let reuse = existing.is_some_and(|e| {
+        e.species == mon.species
+            && e.gender == mon.gender
+            && (mon.nature.is_none() || e.nature == mon.nature)
+    });
+    if !reuse {
+        new_box_mon(raw, mon, index, ot_id, ot_name);
+    }
+
+    let personality = read_u32(raw, 0);
+    let key = personality ^ read_u32(raw, 4);
+
+    let mut secure = [0; BOX_MON_SECURE_SIZE];
+    secure
+        .copy_from_slice(&raw[BOX_MON_SECURE_OFFSET..BOX_MON_SECURE_OFFSET + BOX_MON_SECURE_SIZE]);
+    crypt_secure(&mut secure, key);
+
+    let growth = substruct_offset(personality, Substruct::Growth);
+    write_u16(&mut secure, growth + 2, held_item_to_game_id(mon.held_item));
+
+    if let Some(ball) = mon.captured_ball {
+        let misc = substruct_offset(personality, Substruct::Misc);
+        let origins = read_u16(&secure, misc + 2) & !ORIGINS_BALL_MASK;
+        let ball_id = captured_ball_to_game_id(ball);
+        write_u16(
+            &mut secure,
+            misc + 2,
+            origins | (ball_id << ORIGINS_BALL_SHIFT),
+        );
+    }
+
+    for (i, character) in mon.name.characters().iter().enumerate() {
+        raw[BOX_MON_NICKNAME_OFFSET + i] = character_to_game_value(*character);
+    }
+
+    if let Some(markings) = mon.markings {
+        raw[BOX_MON_MARKINGS_OFFSET] = markings.to_u8();
+    }
+
+    write_u16(raw, BOX_MON_CHECKSUM_OFFSET, secure_checksum(&secure));
+    crypt_secure(&mut secure, key);
+    raw[BOX_MON_SECURE_OFFSET..BOX_MON_SECURE_OFFSET + BOX_MON_SECURE_SIZE]
+        .copy_from_slice(&secure);
+}
+
+fn new_box_mon(
+    raw: &mut [u8],
+    mon: &BoxMon,
+    index: usize,
+    ot_id: u32,
+    ot_name: &[u8; TRAINER_NAME_LENGTH],
+) {
+    raw.fill(0);
+
+    // Any personality works as long as the low byte lands on the right gender
+    let mut personality = (index as u32).wrapping_mul(0x9E37_79B9) & !0xFF;
+    personality |= match mon.gender {
+        BoxMonGender::Male => 0xFF,
+        BoxMonGender::Female => 0x00,
+    };
+    // Stepping the upper bytes cycles through every nature since 0x100 and 25 are coprime
+    if let Some(nature) = mon.nature {
+        while personality % NATURE_COUNT != nature.to_u8() as u32 {
+            personality = personality.wrapping_add(0x100);
+        }
+    }
+
+    write_u32(raw, 0, personality);
+    write_u32(raw, 4, ot_id);
+    raw[BOX_MON_LANGUAGE_OFFSET] = LANGUAGE_ENGLISH;
+    raw[BOX_MON_FLAGS_OFFSET] = FLAG_HAS_SPECIES;
+    raw[BOX_MON_OT_NAME_OFFSET..BOX_MON_OT_NAME_OFFSET + TRAINER_NAME_LENGTH]
+        .copy_from_slice(ot_name);
+
+    let (species_id, experience, first_move) = species_to_game_info(mon.species);
+
+    let mut secure = [0; BOX_MON_SECURE_SIZE];
+    let growth = substruct_offset(personality, Substruct::Growth);
+    write_u16(&mut secure, growth, species_id);
+    write_u32(&mut secure, growth + 4, experience);
+    secure[growth + 9] = BASE_FRIENDSHIP;
+
+    let attacks = substruct_offset(personality, Substruct::Attacks);
+    write_u16(&mut secure, attacks, first_move);
+    secure[attacks + 8] = 35;
+
+    let misc = substruct_offset(personality, Substruct::Misc);
+    secure[misc + 1] = MET_LOCATION_ROUTE_116;
+    write_u16(
+        &mut secure,
+        misc + 2,
+        MET_LEVEL | (ORIGIN_GAME_EMERALD << 7) | (POKE_BALL << ORIGINS_BALL_SHIFT),
+    );
+
+    write_u16(raw, BOX_MON_CHECKSUM_OFFSET, secure_checksum(&secure));
+    crypt_secure(&mut secure, personality ^ ot_id);
+    raw[BOX_MON_SECURE_OFFSET..BOX_MON_SECURE_OFFSET + BOX_MON_SECURE_SIZE]
+        .copy_from_slice(&secure);
+}
+
+fn decode_pc_box(buffer: &[u8], box_index: usize) -> Result<PcBox, SaveError> {
+    let offset = PC_BOX_NAMES_OFFSET + box_index * PC_BOX_NAME_SIZE;
+    let mut name = [BoxMonCharacter::default(); BOX_NAME_LENGTH];
+    for (i, character) in name.iter_mut().enumerate() {
+        *character = match character_from_game_value(buffer[offset + i]) {
+            Some(character) => character,
+            None => return Err(SaveError::UnsupportedBoxName { box_index }),
+        };
+    }
+
+    let wallpaper = buffer[PC_BOX_WALLPAPERS_OFFSET + box_index];
+    match BoxWallpaper::from_repr(wallpaper) {
+        Some(wallpaper) => Ok(PcBox {
+            name: BoxName::new(name),
+            wallpaper,
+        }),
+        None => Err(SaveError::UnsupportedWallpaper {
+            box_index,
+            wallpaper,
+        }),
+    }
+}
+
+fn encode_pc_box(buffer: &mut [u8], box_index: usize, pc_box: &PcBox) {
+    let offset = PC_BOX_NAMES_OFFSET + box_index * PC_BOX_NAME_SIZE;
+    for (i, character) in pc_box.name.characters().iter().enumerate() {
+        buffer[offset + i] = character_to_game_value(*character);
+    }
+    buffer[offset + BOX_NAME_LENGTH] = STRING_TERMINATOR;
+    buffer[PC_BOX_WALLPAPERS_OFFSET + box_index] = pc_box.wallpaper.to_u8();
+}
+
+fn gender_from_personality(personality: u32) -> BoxMonGender {
+    if ((personality & 0xFF) as u8) < GENDER_RATIO {
+        BoxMonGender::Female
+    } else {
+        BoxMonGender::Male
+    }
+}
+
+fn species_from_game_id(id: u16) -> Option<BoxMonSpecies> {
+    Some(match id {
+        286 => BoxMonSpecies::POOCHYENA,
+        301 => BoxMonSpecies::NINCADA,
+        370 => BoxMonSpecies::WHISMUR,
+        304 => BoxMonSpecies::TAILLOW,
+        _ => return None,
+    })
+}
+
+// Species ID, experience at level 5 and a level 5 move
+fn species_to_game_info(species: BoxMonSpecies) -> (u16, u32, u16) {
+    match species {
+        BoxMonSpecies::POOCHYENA => (286, 125, 33),
+        BoxMonSpecies::NINCADA => (301, 237, 10),
+        BoxMonSpecies::WHISMUR => (370, 135, 1),
+        BoxMonSpecies::TAILLOW => (304, 135, 64),
+    }
+}
+
+const HELD_ITEM_GAME_IDS: [(BoxMonHeldItem, u16); 33] = [
+    (BoxMonHeldItem::Empty, 0),
+    (BoxMonHeldItem::Antidote, 14),
+    (BoxMonHeldItem::Awakening, 17),
+    (BoxMonHeldItem::BurnHeal, 15),
+    (BoxMonHeldItem::DireHit, 74),
+    (BoxMonHeldItem::EnergyPower, 30),
+    (BoxMonHeldItem::EnergyRoot, 31),
+    (BoxMonHeldItem::EscapeRope, 85),
+    (BoxMonHeldItem::FluffyTail, 81),
+    (BoxMonHeldItem::FullHeal, 23),
+    (BoxMonHeldItem::FullRestore, 19),
+    (BoxMonHeldItem::GreatBall, 3),
+    (BoxMonHeldItem::GuardSpec, 73),
+    (BoxMonHeldItem::HealPowder, 32),
+    (BoxMonHeldItem::HyperPotion, 21),
+    (BoxMonHeldItem::IceHeal, 16),
+    (BoxMonHeldItem::MaxPotion, 20),
+    (BoxMonHeldItem::MaxRepel, 84),
+    (BoxMonHeldItem::ParlyzHeal, 18),
+    (BoxMonHeldItem::PokeBall, 4),
+    (BoxMonHeldItem::Potion, 13),
+    (BoxMonHeldItem::NestBall, 8),
+    (BoxMonHeldItem::Repel, 86),
+    (BoxMonHeldItem::RevivalHerb, 33),
+    (BoxMonHeldItem::Revive, 24),
+    (BoxMonHeldItem::SuperPotion, 22),
+    (BoxMonHeldItem::SuperRepel, 83),
+    (BoxMonHeldItem::TimerBall, 10),
+    (BoxMonHeldItem::XAccuracy, 78),
+    (BoxMonHeldItem::XAttack, 75),
+    (BoxMonHeldItem::XDefend, 76),
+    (BoxMonHeldItem::XSpecial, 79),
+    (BoxMonHeldItem::XSpeed, 77),
+];
+
+const CAPTURED_BALL_GAME_IDS: [(BoxMonCapturedBall, u16); 8] = [
+    (BoxMonCapturedBall::ItemUltraBall, 2),
+    (BoxMonCapturedBall::ItemGreatBall, 3),
+    (BoxMonCapturedBall::ItemPokeBall, 4),
+    (BoxMonCapturedBall::ItemNetBall, 6),
+    (BoxMonCapturedBall::ItemDiveBall, 7),
+    (BoxMonCapturedBall::ItemNestBall, 8),
+    (BoxMonCapturedBall::ItemRepeatBall, 9),
+    (BoxMonCapturedBall::ItemTimerBall, 10),
+];
+
+// Balls mon-fs doesn't use read as no ball
+fn captured_ball_from_game_id(id: u16) -> Option<BoxMonCapturedBall> {
+    CAPTURED_BALL_GAME_IDS
+        .iter()
+        .find(|(_, game_id)| *game_id == id)
+        .map(|(ball, _)| *ball)
+}
+
+fn captured_ball_to_game_id(ball: BoxMonCapturedBall) -> u16 {
+    CAPTURED_BALL_GAME_IDS
+        .iter()
+        .find(|(b, _)| *b == ball)
+        .map(|(_, game_id)| *game_id)
+        .unwrap()
+}
+
+fn held_item_from_game_id(id: u16) -> Option<BoxMonHeldItem> {
+    HELD_ITEM_GAME_IDS
+        .iter()
+        .find(|(_, game_id)| *game_id == id)
+        .map(|(item, _)| *item)
+}
+
+fn held_item_to_game_id(item: BoxMonHeldItem) -> u16 {
+    HELD_ITEM_GAME_IDS
+        .iter()
+        .find(|(i, _)| *i == item)
+        .map(|(_, game_id)| *game_id)
+        .unwrap()
+}
+
+fn character_from_game_value(value: u8) -> Option<BoxMonCharacter> {
+    let text = match value {
+        0xBB..=0xD4 => ((b'A' + (value - 0xBB)) as char).to_string(),
+        0xD5..=0xEE => ((b'a' + (value - 0xD5)) as char).to_string(),
+        0xA1..=0xAA => ((b'0' + (value - 0xA1)) as char).to_string(),
+        0x00 => " ".to_string(),
+        0xAB => "!".to_string(),
+        0xAC => "?".to_string(),
+        0xAD => ".".to_string(),
+        0xAE => "-".to_string(),
+        0xB0 => "…".to_string(),
+        0xB1 => "“".to_string(),
+        0xB2 => "”".to_string(),
+        0xB3 => "‘".to_string(),
+        0xB4 => "’".to_string(),
+        0xB5 => "♂".to_string(),
+        0xB6 => "♀".to_string(),
+        0xB8 => ",".to_string(),
+        0xBA => "/".to_string(),
+        _ => return None,
+    };
+
+    crate::mon_field::FromStringInput::try_from_string(&text)
+}
+
+fn character_to_game_value(character: BoxMonCharacter) -> u8 {
+    let text = character.to_string();
+    let c = text.chars().next().unwrap();
+    match c {
+        'A'..='Z' => 0xBB + (c as u8 - b'A'),
+        'a'..='z' => 0xD5 + (c as u8 - b'a'),
+        '0'..='9' => 0xA1 + (c as u8 - b'0'),
+        ' ' => 0x00,
+        '!' => 0xAB,
+        '?' => 0xAC,
+        '.' => 0xAD,
+        '-' => 0xAE,
+        '…' => 0xB0,
+        '“' => 0xB1,
+        '”' => 0xB2,
+        '‘' => 0xB3,
+        '’' => 0xB4,
+        '♂' => 0xB5,
+        '♀' => 0xB6,
+        ',' => 0xB8,
+        '/' => 0xBA,
+        _ => STRING_TERMINATOR,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        box_mon::{MonLayout, MonPacking},
+        file_pc::{FilePc, PcEncodeOptions},
+        mon_field::FromStringInput,
+    };
+
+    use super::*;
+
+    #[test]
+    fn full_alphabet_game_values_round_trip() {
+        for character in crate::mon_name::NameAlphabet::Full.characters() {
+            let value = character_to_game_value(character);
+            assert_ne!(value, STRING_TERMINATOR);
+            assert_eq!(character_from_game_value(value), Some(character));
+        }
+    }
+
+    // Built by test_assets/make_emerald_synthetic.py
+    const SYNTHETIC_SAVE: &[u8] = include_bytes!("../../test_assets/emerald_synthetic.sav");
+
+    #[test]
+    fn read_synthetic_save() {
+        let save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        assert_eq!(save.trainer_id(), 0x0BADF00D);
+
+        let pc = save.to_pc().unwrap();
+        assert_eq!(pc.mons.iter().filter(|m| m.is_some()).count(), 4);
+
+        let mon = pc.mons[1].unwrap();
+        assert_eq!(mon.species, BoxMonSpecies::NINCADA);
+        assert_eq!(mon.gender, BoxMonGender::Female);
+        assert_eq!(mon.name, BoxMonName::try_from_string("Heyyo?…♂/2").unwrap());
+        assert_eq!(mon.held_item, BoxMonHeldItem::Potion);
+
+        let mon = pc.mons[2].unwrap();
+        assert_eq!(mon.species, BoxMonSpecies::WHISMUR);
+        assert_eq!(mon.gender, BoxMonGender::Male);
+        assert_eq!(mon.held_item, BoxMonHeldItem::TimerBall);
+
+        let mon = pc.mons[PC_BOX_SIZE + 5].unwrap();
+        assert_eq!(mon.species, BoxMonSpecies::TAILLOW);
+        assert_eq!(mon.gender, BoxMonGender::Female);
+        assert_eq!(mon.name, BoxMonName::try_from_string("zzzzzzzzzz").unwrap());
+        assert_eq!(mon.held_item, BoxMonHeldItem::NestBall);
+    }
+
+    #[test]
+    fn reject_bad_saves() {
+        assert_eq!(
+            EmeraldSave::new(vec![0; 100]).unwrap_err(),
+            SaveError::BadSize(100)
+        );
+        assert_eq!(
+            EmeraldSave::new(vec![0; SAVE_SIZE]).unwrap_err(),
+            SaveError::NoValidSlot
+        );
+    }
+
+    #[test]
+    fn falls_back_to_older_slot_on_bad_checksum() {
+        let mut raw = SYNTHETIC_SAVE.to_vec();
+        // Corrupt a byte in the newer slot
+        raw[SLOT_SIZE + 0x10] ^= 0xFF;
+
+        let pc = EmeraldSave::new(raw).unwrap().to_pc().unwrap();
+        assert_eq!(pc.mons.iter().filter(|m| m.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn write_file_pc_to_save() {
+        let mut file_pc = FilePc::new();
+        file_pc
+            .add_file_raw(
+                "ricky.webp",
+                include_bytes!("../../test_assets/ricky.webp").to_vec(),
+            )
+            .unwrap();
+        let pc = file_pc.as_pc().unwrap();
+
+        let mut save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        save.write_pc(&pc).unwrap();
+
+        let reloaded = EmeraldSave::new(save.into_bytes()).unwrap();
+        let read_back = reloaded.to_pc().unwrap();
+        assert_eq!(read_back.mons, pc.mons);
+
+        let file_pc = FilePc::new_from_pc(read_back).unwrap();
+        assert_eq!(
+            file_pc.files[0].get_data().unwrap(),
+            include_bytes!("../../test_assets/ricky.webp").to_vec()
+        );
+    }
+
+    #[test]
+    fn captured_ball_round_trips_through_save() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a", vec![7; 200]).unwrap();
+        let pc = file_pc
+            .as_pc_with_options(&PcEncodeOptions {
+                layout: MonLayout {
+                    captured_ball: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(pc.mons[1].unwrap().captured_ball.is_some());
+
+        let mut save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        save.write_pc(&pc).unwrap();
+        assert_eq!(save.to_pc().unwrap().mons, pc.mons);
+
+        // Without the header flag the balls are left out
+        let mut pc = save.to_pc().unwrap();
+        pc.set_layout(&MonLayout::default()).unwrap();
+        save.write_pc(&pc).unwrap();
+        assert!(save.to_pc().unwrap().mons[1]
+            .unwrap()
+            .captured_ball
+            .is_none());
+    }
+
+    #[test]
+    fn markings_and_nature_round_trip_through_save() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a", vec![7; 200]).unwrap();
+        let pc = file_pc
+            .as_pc_with_options(&PcEncodeOptions {
+                layout: MonLayout {
+                    packing: MonPacking::MixedRadix(30),
+                    markings: true,
+                    nature: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        save.write_pc(&pc).unwrap();
+        let read_back = save.to_pc().unwrap();
+        assert_eq!(read_back.mons, pc.mons);
+        assert!(read_back.mons[0].unwrap().nature.is_none());
+        assert!(read_back.mons[1].unwrap().markings.is_some());
+    }
+
+    #[test]
+    fn box_titles_round_trip_through_save() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a", vec![7; 200]).unwrap();
+        let pc = file_pc
+            .as_pc_with_options(&PcEncodeOptions {
+                box_data: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(pc.boxes.len(), NUM_PC_BOXES);
+
+        let mut save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        save.write_pc(&pc).unwrap();
+        let read_back = save.to_pc().unwrap();
+        assert_eq!(read_back.boxes, pc.boxes);
+
+        let file_pc = FilePc::new_from_pc(read_back).unwrap();
+        assert_eq!(file_pc.files[0].get_data().unwrap(), vec![7; 200]);
+    }
+
+    #[test]
+    fn write_keeps_matching_mons() {
+        let mut save = EmeraldSave::new(SYNTHETIC_SAVE.to_vec()).unwrap();
+        let mut pc = save.to_pc().unwrap();
+        let personality = read_u32(&save.pc_buffer(), PC_MONS_OFFSET + BOX_MON_SIZE);
+
+        let mut mon = pc.mons[1].unwrap();
+        mon.held_item = BoxMonHeldItem::Repel;
+        pc.set_mon(0, 1, mon);
+        save.write_pc(&pc).unwrap();
+
+        assert_eq!(
+            read_u32(&save.pc_buffer(), PC_MONS_OFFSET + BOX_MON_SIZE),
+            personality
+        );
+        assert_eq!(save.to_pc().unwrap().mons[1].unwrap(), mon);
+    }
+}
@@ -0,0 +1,293 @@
+use serde_derive::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::box_mon::BoxMon;
+use crate::error::{GameValueError, MonFsError};
+use crate::mon_field::{
+    bits_to_digits, digits_to_bits, mixed_radix_bit_counts, FromGameValueError, ToGameValueError,
+};
+use crate::mon_held_item::BoxMonHeldItem;
+use crate::pc::{MonPosition, PC, PC_FLAG_STORAGE};
+use crate::BoxMonBitVec;
+
+pub const PARTY_SIZE: usize = 6;
+pub const NUM_ITEM_PC_SLOTS: usize = 50;
+// Quantities run from 1 to 999, 0 leaves the item out of the item PC
+const ITEM_QUANTITY_RADIX: u128 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemPcEntry {
+    pub item: BoxMonHeldItem,
+    pub quantity: u16,
+}
+
+// The PC plus the party and the item PC. Data fills the PC first and only carries on into
+// the party then the item PC when PC_FLAG_STORAGE is set. Serialises as the PC with extra
+// fields so a PC json is also a Storage json.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Storage {
+    #[serde(flatten)]
+    pub pc: PC,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub party: Vec<BoxMon>,
+    // Deposited items, the game stacks each item into one slot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub item_pc: Vec<ItemPcEntry>,
+    #[serde(skip)]
+    current_read_offset: usize,
+}
+
+impl Storage {
+    pub fn new(pc: PC) -> Storage {
+        Storage {
+            pc,
+            ..Default::default()
+        }
+    }
+
+    fn uses_storage(&self) -> Result<bool, MonFsError> {
+        Ok(self.pc.get_flags()? & PC_FLAG_STORAGE != 0)
+    }
+
+    // The item PC holds a quantity of every item mons can hold
+    fn items() -> impl Iterator<Item = BoxMonHeldItem> {
+        BoxMonHeldItem::iter().filter(|item| *item != BoxMonHeldItem::Empty)
+    }
+
+    // Party mons use the PC's layout as one mixed radix number
+    fn party_radix(&self) -> Result<u128, MonFsError> {
+        Ok(self.pc.get_layout()?.possible_values())
+    }
+
+    fn party_capacity_bytes(&self) -> Result<usize, MonFsError> {
+        if !self.uses_storage()? {
+            return Ok(0);
+        }
+        Ok(mixed_radix_bit_counts(self.party_radix()?, PARTY_SIZE)[PARTY_SIZE] / 8)
+    }
+
+    fn item_capacity_bytes(&self) -> Result<usize, MonFsError> {
+        if !self.uses_storage()? {
+            return Ok(0);
+        }
+        let items = Self::items().count();
+        Ok(mixed_radix_bit_counts(ITEM_QUANTITY_RADIX, items)[items] / 8)
+    }
+
+    // Bytes the party and item PC can hold on top of the PC
+    pub fn capacity_bytes(&self) -> Result<usize, MonFsError> {
+        Ok(self.party_capacity_bytes()? + self.item_capacity_bytes()?)
+    }
+
+    pub fn remaining_bytes(&self) -> Result<usize, MonFsError> {
+        let used = self.pc.get_storage_bytes()?;
+        Ok(self.pc.remaining_bytes()? + self.capacity_bytes()? - used)
+    }
+
+    // Position of the mon holding the given bit, None for bits in the party or item PC
+    pub fn mon_position_for_bit(&mut self, bit: usize) -> Result<Option<MonPosition>, MonFsError> {
+        if bit >= self.pc.data()?.len() * 8 {
+            return Ok(None);
+        }
+        self.pc.mon_position_for_bit(bit)
+    }
+
+    fn storage_data(&self) -> Result<Vec<u8>, MonFsError> {
+        let used = self.pc.get_storage_bytes()?;
+        if used == 0 {
+            return Ok(vec![]);
+        }
+
+        let party_bytes = used.min(self.party_capacity_bytes()?);
+        if self.party.len() != PARTY_SIZE {
+            return Err(MonFsError::BadStorage(GameValueError::ToGameValue(
+                ToGameValueError::BadBitsLength,
+            )));
+        }
+
+        let layout = self.pc.get_layout()?;
+        let mut digits = vec![];
+        for mon in &self.party {
+            digits.push(layout.mon_to_value(mon).map_err(from_game_value)?);
+        }
+        let mut data = digits_to_bytes(&digits, self.party_radix()?, party_bytes)?;
+
+        if used > party_bytes {
+            let mut quantities = vec![0; Self::items().count()];
+            for entry in &self.item_pc {
+                let slot = Self::items().position(|item| item == entry.item);
+                match slot {
+                    Some(slot) if quantities[slot] == 0 && entry.quantity > 0 => {
+                        quantities[slot] = entry.quantity as u128
+                    }
+                    _ => return Err(from_game_value(FromGameValueError::UnsupportedValue)),
+                }
+            }
+            data.extend(digits_to_bytes(
+                &quantities,
+                ITEM_QUANTITY_RADIX,
+                used - party_bytes,
+            )?);
+        }
+
+        Ok(data)
+    }
+
+    fn write_storage_data(&mut self, data: &[u8]) -> Result<(), MonFsError> {
+        let party_capacity = self.party_capacity_bytes()?;
+        let party_bytes = data.len().min(party_capacity);
+
+        let layout = self.pc.get_layout()?;
+        let digits = bytes_to_digits(
+            &data[..party_bytes],
+            self.party_radix()?,
+            PARTY_SIZE,
+            party_capacity,
+        )?;
+        let mut party = vec![];
+        for digit in digits {
+            party.push(layout.value_to_mon(digit).map_err(to_game_value)?);
+        }
+
+        let mut item_pc = vec![];
+        if data.len() > party_bytes {
+            let quantities = bytes_to_digits(
+                &data[party_bytes..],
+                ITEM_QUANTITY_RADIX,
+                Self::items().count(),
+                self.item_capacity_bytes()?,
+            )?;
+            for (item, quantity) in Self::items().zip(quantities) {
+                if quantity > 0 {
+                    item_pc.push(ItemPcEntry {
+                        item,
+                        quantity: quantity as u16,
+                    });
+                }
+            }
+        }
+
+        self.party = party;
+        self.item_pc = item_pc;
+        self.pc.set_storage_bytes(data.len())
+    }
+}
+
+fn from_game_value(err: FromGameValueError) -> MonFsError {
+    MonFsError::BadStorage(GameValueError::FromGameValue(err))
+}
+
+fn to_game_value(err: ToGameValueError) -> MonFsError {
+    MonFsError::BadStorage(GameValueError::ToGameValue(err))
+}
+
+// Bytes as a mixed radix number, zero padded up to the capacity
+fn bytes_to_digits(
+    data: &[u8],
+    radix: u128,
+    digits: usize,
+    capacity_bytes: usize,
+) -> Result<Vec<u128>, MonFsError> {
+    let mut bits = BoxMonBitVec::new_from_raw(data);
+    bits.0.grow(capacity_bytes * 8 - bits.0.len(), false);
+    bits_to_digits(&bits, radix, digits).map_err(from_game_value)
+}
+
+fn digits_to_bytes(digits: &[u128], radix: u128, bytes: usize) -> Result<Vec<u8>, MonFsError> {
+    match digits_to_bits(digits, radix, bytes * 8) {
+        Ok(bits) => Ok(bits.to_raw()),
+        Err(err) => Err(to_game_value(err)),
+    }
+}
+
+impl std::io::Write for Storage {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let remaining_bytes = self.remaining_bytes()?;
+        if remaining_bytes < buf.len() {
+            return Err(MonFsError::CapacityExceeded {
+                have: remaining_bytes,
+                need: buf.len(),
+            }
+            .into());
+        }
+
+        let in_pc = self.pc.remaining_bytes()?.min(buf.len());
+        if in_pc > 0 {
+            self.pc.write_all(&buf[..in_pc])?;
+        }
+
+        if in_pc < buf.len() {
+            let mut data = self.storage_data()?;
+            data.extend_from_slice(&buf[in_pc..]);
+            self.write_storage_data(&data)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Read for Storage {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut data = self.pc.data()?;
+        data.extend(self.storage_data()?);
+
+        let start = self.current_read_offset.min(data.len());
+        let end = (start + buf.len()).min(data.len());
+        buf[..end - start].copy_from_slice(&data[start..end]);
+        self.current_read_offset = end;
+
+        Ok(end - start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::box_mon::{MonLayout, MonPacking};
+
+    #[test]
+    fn data_carries_on_into_the_party_and_item_pc() {
+        for packing in [MonPacking::Bits, MonPacking::MixedRadix(30)] {
+            let mut pc = PC::new();
+            pc.set_layout(&MonLayout {
+                packing,
+                ..Default::default()
+            })
+            .unwrap();
+            let without = Storage::new(pc.clone());
+            assert_eq!(without.capacity_bytes().unwrap(), 0);
+
+            pc.set_flags(PC_FLAG_STORAGE).unwrap();
+            let mut storage = Storage::new(pc);
+            // 6 * 68 bits in the party and 32 * log2(1000) in the item PC
+            assert_eq!(storage.capacity_bytes().unwrap(), 51 + 39);
+
+            let data: Vec<u8> = (0..storage.remaining_bytes().unwrap())
+                .map(|i| (i * 7) as u8)
+                .collect();
+            let pc_bytes = storage.pc.remaining_bytes().unwrap();
+            storage.write_all(&data[..pc_bytes + 10]).unwrap();
+            assert_eq!(storage.party.len(), PARTY_SIZE);
+            assert!(storage.item_pc.is_empty());
+            storage.write_all(&data[pc_bytes + 10..]).unwrap();
+            assert!(!storage.item_pc.is_empty());
+            assert!(storage.item_pc.len() <= NUM_ITEM_PC_SLOTS);
+            assert_eq!(storage.remaining_bytes().unwrap(), 0);
+            assert!(storage.write_all(&[0]).is_err());
+
+            // Survives a trip through json
+            let json = serde_json::to_string(&storage).unwrap();
+            let mut storage: Storage = serde_json::from_str(&json).unwrap();
+            let mut buf = vec![];
+            storage.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, data);
+            assert_eq!(storage.mon_position_for_bit(pc_bytes * 8).unwrap(), None);
+        }
+    }
+}
@@ -47,10 +47,26 @@ export interface BoxMon {
   gender: 'Male' | 'Female'
   name: string
   held_item: string
+  captured_ball?: string
+  markings?: string
+  nature?: string
+}
+
+export interface PcBox {
+  name: string
+  wallpaper: string
+}
+
+export interface ItemPcEntry {
+  item: string
+  quantity: number
 }
 
 export interface PC {
   mons: (BoxMon | null)[]
+  boxes?: PcBox[]
+  party?: BoxMon[]
+  item_pc?: ItemPcEntry[]
 }
 
 export interface WebBoxMon extends BoxMon {
@@ -1,9 +1,14 @@
-import { BOX_COUNT, type BoxMon, type WebBoxMon } from '@/pc'
+import { BOX_COUNT, type BoxMon, type ItemPcEntry, type PcBox, type WebBoxMon } from '@/pc'
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
 
 export const usePcStore = defineStore('pc', () => {
   const mons = ref<WebBoxMon[]>([])
+  // Titles and wallpapers to set on each box, empty when the PC doesn't use them
+  const boxes = ref<PcBox[]>([])
+  // Only used once the PC is full
+  const party = ref<BoxMon[]>([])
+  const itemPc = ref<ItemPcEntry[]>([])
   const currentBox = ref(0)
 
   function setMons(new_mons: (BoxMon | WebBoxMon | null)[]) {
@@ -24,8 +29,22 @@ export const usePcStore = defineStore('pc', () => {
     })
   }
 
+  function setBoxes(new_boxes: PcBox[]) {
+    boxes.value = new_boxes
+  }
+
+  function setPartyAndItems(new_party: BoxMon[], new_item_pc: ItemPcEntry[]) {
+    party.value = new_party
+    itemPc.value = new_item_pc
+  }
+
   function pcJson() {
-    return JSON.stringify({ mons: mons.value })
+    return JSON.stringify({
+      mons: mons.value,
+      boxes: boxes.value,
+      party: party.value,
+      item_pc: itemPc.value
+    })
   }
 
   function neededItemsAll() {
@@ -197,6 +216,9 @@ export const usePcStore = defineStore('pc', () => {
 
   return {
     mons,
+    boxes,
+    party,
+    itemPc,
     currentBox,
     neededItemsAll,
     neededItemsBox,
@@ -204,6 +226,8 @@ export const usePcStore = defineStore('pc', () => {
     neededMonsBox,
     filledMonCount,
     setMons,
+    setBoxes,
+    setPartyAndItems,
     toggleCaught,
     addMon,
     removeMon,
@@ -1,11 +1,11 @@
-use std::process::Command;
+use std::path::Path;
 
 use mon_fs_box::{
     box_mon::{BoxMon, StringMonParseError, StringsMon},
     file_pc::FilePc,
+    mon_name::NameAlphabet,
     pc::PC,
 };
-use serde::Deserialize;
 
 use crate::{options::OptionsDecode, ProgramError};
 
@@ -19,14 +19,14 @@ pub fn decode_pc_files(pc: &FilePc, options: &OptionsDecode) -> Result<(), Progr
         )));
     }
 
-    pc.write_to_folder(decode_path);
+    pc.write_to_folder(decode_path)?;
 
     println!("Decoded PC files to: {}", decode_path.display());
 
     Ok(())
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 struct DecoderOutput {
     boxes: Vec<Vec<StringsMon>>,
 }
@@ -38,14 +38,7 @@ impl TryInto<PC> for DecoderOutput {
         let mut pc = PC::new();
         for (box_index, box_mon) in self.boxes.into_iter().enumerate() {
             for (mon_index, mon) in box_mon.into_iter().enumerate() {
-                pc.set_mon(
-                    box_index,
-                    mon_index,
-                    match BoxMon::try_from_strings_mon(mon) {
-                        Ok(mon) => mon,
-                        Err(err) => return Err(err),
-                    },
-                );
+                pc.set_mon(box_index, mon_index, BoxMon::try_from_strings_mon(mon)?);
             }
         }
 
@@ -53,9 +46,10 @@ impl TryInto<PC> for DecoderOutput {
     }
 }
 
-pub fn load_pc_from_screenshots(options: &OptionsDecode) -> Result<PC, ProgramError> {
-    let pc_screenshots = std::fs::canonicalize(&options.pc_screenshots).unwrap();
-
+pub fn load_pc_from_screenshots(
+    pc_screenshots: &Path,
+    alphabet: NameAlphabet,
+) -> Result<PC, ProgramError> {
     if !pc_screenshots.exists() || !pc_screenshots.is_dir() {
         return Err(ProgramError::BadPathGiven(format!(
             "{}",
@@ -63,31 +57,16 @@ pub fn load_pc_from_screenshots(options: &OptionsDecode) -> Result<PC, ProgramEr
         )));
     }
 
-    let output: std::process::Output = Command::new("poetry")
-        .arg("run")
-        .arg("decoder")
-        .current_dir(options.python_script_path.as_os_str())
-        .env(
-            "PC_DEC_SCREENSHOT_FOLDER",
-            pc_screenshots.display().to_string(),
-        )
-        .output()
-        .unwrap();
-
-    if !output.status.success() {
-        return Err(ProgramError::DecoderFailure(format!(
-            "{}",
-            String::from_utf8_lossy(&output.stderr)
-        )));
-    }
+    let boxes = match mon_fs_pc_decoder::decode_folder_with_alphabet(pc_screenshots, alphabet) {
+        Ok(boxes) => boxes,
+        Err(err) => return Err(ProgramError::DecoderFailure(format!("{:?}", err))),
+    };
 
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+    let output = DecoderOutput { boxes };
+    println!("{}", serde_json::to_string(&output.boxes).unwrap());
 
-    let output = String::from_utf8_lossy(&output.stdout).to_string();
-
-    let output: DecoderOutput = serde_json::from_str(&output).unwrap();
-
-    let pc: PC = output.try_into().unwrap();
-
-    Ok(pc)
+    match output.try_into() {
+        Ok(pc) => Ok(pc),
+        Err(err) => Err(ProgramError::DecoderFailure(format!("{:?}", err))),
+    }
 }
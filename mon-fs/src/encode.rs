@@ -16,27 +16,22 @@ pub fn encode_file_to_file_pc(
     }
 
     if encode_file.is_dir() {
-        let files = std::fs::read_dir(encode_file).unwrap();
-
-        for file in files {
-            let file = file.unwrap();
-            let file_path = file.path();
-
-            if file_path.is_dir() {
-                continue;
-            }
-
-            let filename_base = file.file_name().to_str().unwrap().to_string();
-
-            if let Err(err) = pc.add_file(&filename_base, &file_path) {
-                return Err(ProgramError::IoError(err));
-            }
+        if let Err(err) = pc.add_folder(encode_file) {
+            return Err(ProgramError::MonFsError(err));
         }
     } else {
-        let filename_base = encode_file.file_name().unwrap().to_str().unwrap();
+        let filename_base = match encode_file.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => {
+                return Err(ProgramError::BadPathGiven(format!(
+                    "{}",
+                    encode_file.display()
+                )))
+            }
+        };
 
-        if let Err(err) = pc.add_file(filename_base, encode_file) {
-            return Err(ProgramError::IoError(err));
+        if let Err(err) = pc.add_file(&filename_base, encode_file) {
+            return Err(ProgramError::MonFsError(err));
         }
     }
 
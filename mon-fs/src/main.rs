@@ -1,6 +1,19 @@
-use std::fs::{self, File};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
 
-use mon_fs_box::{file_pc::FilePc, pc::PC};
+use mon_fs_box::{
+    box_mon::{MonLayout, MonPacking},
+    error::MonFsError,
+    file_pc::{FilePc, PcEncodeOptions, PcFileInfo},
+    pc::{MonPosition, PcUsage},
+    pc_set::PcSet,
+    plan::MigrationPlan,
+    reuse::reuse_owned_mons,
+    storage::Storage,
+};
+use serde::Serialize;
 use structopt::StructOpt;
 
 mod decode;
@@ -19,72 +32,447 @@ enum ProgramError {
     DecoderFailure(String),
     #[allow(dead_code)]
     IoError(std::io::Error),
+    #[allow(dead_code)]
+    MonFsError(MonFsError),
 }
 
-fn main() -> Result<(), ProgramError> {
-    let options = options::Options::from_args();
+impl From<std::io::Error> for ProgramError {
+    fn from(value: std::io::Error) -> Self {
+        ProgramError::IoError(value)
+    }
+}
 
-    let mut file_pc = if options.pc_file.exists() {
-        let existing = match fs::read(&options.pc_file) {
-            Ok(data) => data,
-            Err(_) => {
+impl From<MonFsError> for ProgramError {
+    fn from(value: MonFsError) -> Self {
+        ProgramError::MonFsError(value)
+    }
+}
+
+// pc.json becomes pc-1.json, pc-2.json... for each PC in a set
+fn set_file_path(pc_file: &Path, sequence: usize) -> PathBuf {
+    let stem = pc_file.file_stem().unwrap_or_default().to_string_lossy();
+    pc_file.with_file_name(format!("{}-{}.json", stem, sequence))
+}
+
+fn set_file_paths(pc_file: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|sequence| set_file_path(pc_file, sequence))
+        .take_while(|path| path.exists())
+        .collect()
+}
+
+fn read_storage(path: &Path) -> Result<Storage, ProgramError> {
+    let bad_guide = || ProgramError::BadGuideFileGiven(format!("{}", path.display()));
+
+    let existing = fs::read(path).map_err(|_| bad_guide())?;
+    serde_json::from_slice::<Storage>(&existing).map_err(|_| bad_guide())
+}
+
+// Written next to path first so a failed write leaves the old guide alone, renamed over
+// it by the caller
+fn write_temp_storage(path: &Path, storage: &Storage) -> Result<PathBuf, ProgramError> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let file = File::create(&temp_path)?;
+    if let Err(err) = serde_json::to_writer(file, storage) {
+        fs::remove_file(&temp_path)?;
+        return Err(ProgramError::IoError(err.into()));
+    }
+
+    Ok(temp_path)
+}
+
+// Everything info prints, one usage for each PC of a set
+#[derive(Serialize)]
+struct PcInfo {
+    pcs: Vec<PcUsage>,
+    files: Vec<PcFileInfo>,
+}
+
+fn read_pc_info(pc_file: &Path, password: Option<&str>) -> Result<PcInfo, ProgramError> {
+    let (pcs, file_pc) = if pc_file.exists() {
+        let storage = read_storage(pc_file)?;
+        let pc = storage.pc.clone();
+        (
+            vec![pc],
+            FilePc::new_from_storage_with_password(storage, password)?.0,
+        )
+    } else {
+        let mut pcs = vec![];
+        for path in set_file_paths(pc_file) {
+            pcs.push(read_storage(&path)?.pc);
+        }
+        if pcs.is_empty() {
+            return Err(ProgramError::BadGuideFileGiven(format!(
+                "{}",
+                pc_file.display()
+            )));
+        }
+        (
+            pcs.clone(),
+            PcSet { pcs }.into_file_pc_with_password(password)?,
+        )
+    };
+
+    let mut usages = vec![];
+    for pc in &pcs {
+        usages.push(pc.usage()?);
+    }
+    Ok(PcInfo {
+        pcs: usages,
+        files: file_pc.file_infos()?,
+    })
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), ProgramError> {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(err) => return Err(ProgramError::IoError(err.into())),
+    }
+    Ok(())
+}
+
+fn print_positions(message: &str, positions: Vec<MonPosition>) {
+    for position in positions {
+        println!(
+            "Box {} slot {} {}",
+            position.box_index + 1,
+            position.slot + 1,
+            message
+        );
+    }
+}
+
+fn verify(pc_file: &Path, options: &options::OptionsVerify) -> Result<(), ProgramError> {
+    let password = options.password.as_deref();
+    let storage = match &options.pc_screenshots {
+        Some(pc_screenshots) => {
+            let pc = decode::load_pc_from_screenshots(pc_screenshots, options.alphabet)?;
+            print_positions("failed its checksum", pc.verify()?.suspect);
+            Some(Storage::new(pc))
+        }
+        None if pc_file.exists() => Some(read_storage(pc_file)?),
+        None => None,
+    };
+
+    let file_pc = match storage {
+        Some(storage) => {
+            let (file_pc, report) = FilePc::new_from_storage_with_password(storage, password)
+                .map_err(|err| {
+                    ProgramError::DecoderFailure(format!("PC data could not be recovered: {}", err))
+                })?;
+            print_positions("was corrected", report.corrected);
+            file_pc
+        }
+        None => {
+            let mut pcs = vec![];
+            for path in set_file_paths(pc_file) {
+                pcs.push(read_storage(&path)?.pc);
+            }
+            if pcs.is_empty() {
                 return Err(ProgramError::BadGuideFileGiven(format!(
                     "{}",
-                    options.pc_file.display()
+                    pc_file.display()
                 )));
             }
-        };
+            PcSet { pcs }.into_file_pc_with_password(password)?
+        }
+    };
 
-        // Decode to PC
-        match serde_json::from_slice::<PC>(&existing) {
-            Ok(pc) => pc.into(),
-            Err(_) => {
-                return Err(ProgramError::BadGuideFileGiven(format!(
-                    "{}",
-                    options.pc_file.display()
-                )));
+    let mut failed = 0;
+    for file in &file_pc.files {
+        match (file.get_data(), file.hash) {
+            (Ok(_), Some(_)) => println!("ok      {}", file.name),
+            (Ok(_), None) => println!("no hash {}", file.name),
+            (Err(err), _) => {
+                failed += 1;
+                println!("FAILED  {}: {}", file.name, err);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(ProgramError::DecoderFailure(format!(
+            "{} of {} files failed",
+            failed,
+            file_pc.files.len()
+        )));
+    }
+    println!("{} files ok", file_pc.files.len());
+    Ok(())
+}
+
+fn main() -> Result<(), ProgramError> {
+    let options = options::Options::from_args();
+
+    // Planning only compares the two guides, the PC file is left alone
+    if let options::Command::Plan(options_plan) = &options.command {
+        let from = read_storage(&options_plan.from)?.pc;
+        let to = read_storage(&options_plan.to)?.pc;
+        let plan = MigrationPlan::new(&from, &to);
+        if plan.is_empty() {
+            println!("Nothing to do");
+        }
+        for (i, action) in plan.actions.iter().enumerate() {
+            println!("{}. {}", i + 1, action);
+        }
+        return Ok(());
+    }
+
+    // Listing only reads the PC file
+    if let options::Command::Ls(options_list) = &options.command {
+        let info = read_pc_info(&options.pc_file, options_list.password.as_deref())?;
+        if options_list.json {
+            return print_json(&info.files);
+        }
+        for file in info.files {
+            println!(
+                "{:>8} {:>8} {:<14} {}{}",
+                file.size,
+                file.stored_size,
+                file.codec.to_string(),
+                file.name,
+                if file.crc_ok { "" } else { " (hash mismatch)" }
+            );
+        }
+        return Ok(());
+    }
+    if let options::Command::Info(options_info) = &options.command {
+        let info = read_pc_info(&options.pc_file, options_info.password.as_deref())?;
+        if options_info.json {
+            return print_json(&info);
+        }
+        for (i, usage) in info.pcs.iter().enumerate() {
+            if info.pcs.len() > 1 {
+                println!("PC {}", i + 1);
+            }
+            println!(
+                "Format version {}, {} profile, flags {:#04x}",
+                usage.version,
+                usage.profile.as_deref().unwrap_or("custom"),
+                usage.flags
+            );
+            println!(
+                "{} of {} data mons used, {} padding bits in the last",
+                usage.used_mons, usage.data_mons, usage.padding_bits
+            );
+            if usage.permutation_mons > 0 {
+                println!("{} mons hold the permutation table", usage.permutation_mons);
             }
+            if usage.box_bytes_used > 0 {
+                println!("{} bytes in the box titles", usage.box_bytes_used);
+            }
+            println!("{} bytes remaining", usage.remaining_bytes);
+        }
+        println!(
+            "{} files, {} bytes stored as {}",
+            info.files.len(),
+            info.files.iter().map(|file| file.size).sum::<usize>(),
+            info.files
+                .iter()
+                .map(|file| file.stored_size)
+                .sum::<usize>()
+        );
+        let mismatched = info.files.iter().filter(|file| !file.crc_ok).count();
+        if mismatched > 0 {
+            println!(
+                "{} files don't match their hash, mon-fs verify has more",
+                mismatched
+            );
         }
+        return Ok(());
+    }
+
+    // Verifying only reads the PC file or screenshots
+    if let options::Command::Verify(options_verify) = &options.command {
+        return verify(&options.pc_file, options_verify);
+    }
+
+    let password = match &options.command {
+        options::Command::Encode(options_encode) => options_encode.password.clone(),
+        options::Command::Decode(options_decode) => options_decode.password.clone(),
+        options::Command::Rm(options_remove) => options_remove.password.clone(),
+        options::Command::Mv(options_move) => options_move.password.clone(),
+        options::Command::Put(options_put) => options_put.password.clone(),
+        _ => None,
+    };
+
+    // Decode to PC, plain PC files read as storage without a party or item PC
+    let set_files = set_file_paths(&options.pc_file);
+    let was_set = !options.pc_file.exists() && !set_files.is_empty();
+    let mut previous = None;
+    // Edits keep the options the PC was written with, encode only keeps its parity
+    let mut encode_options = PcEncodeOptions::default();
+    let mut file_pc = if options.pc_file.exists() {
+        let storage = read_storage(&options.pc_file)?;
+        previous = Some(storage.pc.clone());
+        encode_options = PcEncodeOptions::from_pc(&storage.pc)?;
+        FilePc::new_from_storage_with_password(storage, password.as_deref())?.0
+    } else if !set_files.is_empty() {
+        let mut pcs = vec![];
+        for path in &set_files {
+            pcs.push(read_storage(path)?.pc);
+        }
+        encode_options = PcEncodeOptions::from_pc(&pcs[0])?;
+        PcSet { pcs }.into_file_pc_with_password(password.as_deref())?
     } else {
         FilePc::new()
     };
+    encode_options.password = password.clone();
 
+    // A set stays a set, it is only collapsed by hand
+    let mut split = was_set;
+    let mut reuse_from = None;
     match options.command {
         options::Command::Encode(options_encode) => {
-            if let Err(err) = encode::encode_file_to_file_pc(&mut file_pc, &options_encode) {
-                return Err(err);
+            encode::encode_file_to_file_pc(&mut file_pc, &options_encode)?;
+            split = options_encode.split || was_set;
+            reuse_from = options_encode.reuse_from.clone();
+            let parity_mons = encode_options.parity_mons;
+            encode_options = match options_encode.profile {
+                Some(profile) => PcEncodeOptions::from(profile),
+                None => PcEncodeOptions {
+                    parity_mons,
+                    layout: MonLayout {
+                        alphabet: options_encode.alphabet,
+                        packing: match options_encode.mixed_radix_run {
+                            Some(run_length) => MonPacking::MixedRadix(run_length),
+                            None => MonPacking::Bits,
+                        },
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            };
+            // Only the layout comes from the profile alone, the rest is added on top
+            if let Some(parity_mons) = options_encode.parity_mons {
+                encode_options.parity_mons = parity_mons;
+            }
+            encode_options.mon_checksums |= options_encode.mon_checksums;
+            encode_options.append_log = options_encode.append_log;
+            encode_options.archive = options_encode.archive;
+            encode_options.password = password;
+        }
+        options::Command::Plan(_)
+        | options::Command::Ls(_)
+        | options::Command::Info(_)
+        | options::Command::Verify(_) => unreachable!(),
+        options::Command::Rm(options_remove) => {
+            file_pc.remove_file(&options_remove.name)?;
+        }
+        options::Command::Mv(options_move) => {
+            file_pc.rename_file(&options_move.from, &options_move.to)?;
+        }
+        options::Command::Put(options_put) => {
+            let name = match (&options_put.name, options_put.source.file_name()) {
+                (Some(name), _) => name.clone(),
+                (None, Some(name)) => name.to_string_lossy().to_string(),
+                (None, None) => {
+                    return Err(ProgramError::BadPathGiven(format!(
+                        "{}",
+                        options_put.source.display()
+                    )))
+                }
+            };
+
+            if options_put.force {
+                file_pc.replace_file_from(&name, &options_put.source)?;
+            } else {
+                file_pc.add_file(&name, &options_put.source)?;
             }
         }
         options::Command::Decode(options_decode) => {
             println!("Parsing screenshots...");
-            let pc = match decode::load_pc_from_screenshots(&options_decode) {
+            let pc = match decode::load_pc_from_screenshots(
+                &options_decode.pc_screenshots,
+                options_decode.alphabet,
+            ) {
                 Ok(pc) => pc,
                 Err(err) => {
                     return Err(err);
                 }
             };
 
-            let file_pc = pc.into();
+            print_positions(
+                "failed its checksum, check it in game",
+                pc.verify()?.suspect,
+            );
 
-            if let Err(err) = decode::decode_pc_files(&file_pc, &options_decode) {
-                return Err(err);
+            let storage = Storage::new(pc);
+            let file_pc = match FilePc::new_from_storage_with_password(storage, password.as_deref())
+            {
+                Ok((file_pc, report)) => {
+                    for position in report.corrected {
+                        println!(
+                            "Corrected box {} slot {}",
+                            position.box_index + 1,
+                            position.slot + 1
+                        );
+                    }
+                    file_pc
+                }
+                Err(err) => {
+                    return Err(ProgramError::DecoderFailure(format!(
+                        "PC data could not be recovered: {}",
+                        err
+                    )));
+                }
+            };
+
+            decode::decode_pc_files(&file_pc, &options_decode)?;
+        }
+    }
+
+    if split {
+        let set = PcSet::from_file_pc(&file_pc, &encode_options)?;
+        let count = set.pcs.len();
+        let mut written = vec![];
+        for (i, pc) in set.pcs.into_iter().enumerate() {
+            let path = set_file_path(&options.pc_file, i + 1);
+            written.push((write_temp_storage(&path, &Storage::new(pc))?, path));
+        }
+        for (temp_path, path) in written {
+            fs::rename(temp_path, path)?;
+        }
+
+        // Only remove what the new set replaced, once all of it is written
+        if was_set {
+            for path in set_files.iter().skip(count) {
+                fs::remove_file(path)?;
             }
+        } else if options.pc_file.exists() {
+            fs::remove_file(&options.pc_file)?;
         }
+
+        return Ok(());
     }
 
-    // Delete old guide file
-    if options.pc_file.exists() {
-        fs::remove_file(&options.pc_file).unwrap();
+    let mut storage = Storage::new(file_pc.as_pc_with_options(&encode_options)?);
+    if let Some(path) = reuse_from {
+        let owned = read_storage(&path)?.pc;
+        storage.pc = reuse_owned_mons(&storage.pc, &owned)?;
     }
 
-    let pc: PC = match file_pc.as_pc() {
-        Ok(pc) => pc,
-        Err(err) => {
-            return Err(ProgramError::IoError(err));
+    // Point out what has to be redone in game
+    if let Some(previous) = previous {
+        let diff = previous.diff(&storage.pc);
+        println!(
+            "{} slots and {} boxes changed",
+            diff.mons.len(),
+            diff.boxes.len()
+        );
+        for position in diff.mons {
+            println!("Box {} slot {}", position.box_index + 1, position.slot + 1);
         }
-    };
-    let file = File::create(options.pc_file).unwrap();
-    serde_json::to_writer(file, &pc).unwrap();
+        for box_index in diff.boxes {
+            println!("Box {} title", box_index + 1);
+        }
+    }
+
+    let temp_path = write_temp_storage(&options.pc_file, &storage)?;
+    fs::rename(temp_path, &options.pc_file)?;
 
     Ok(())
 }
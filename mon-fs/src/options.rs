@@ -1,5 +1,9 @@
 use std::path::PathBuf;
 
+use mon_fs_box::{
+    mon_name::NameAlphabet,
+    profile::{profile_by_name, EncodingProfile},
+};
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -7,25 +11,197 @@ use structopt::StructOpt;
 pub struct OptionsEncode {
     #[structopt(short, long, parse(from_os_str))]
     pub to_encode: PathBuf,
+
+    /// Mons spent on error correction parity so misread mons can be repaired, defaults to what
+    /// the PC already has
+    #[structopt(long)]
+    pub parity_mons: Option<usize>,
+
+    /// Reserve bits in each mon for a checksum so misread mons can be pointed out
+    #[structopt(long)]
+    pub mon_checksums: bool,
+
+    /// Characters used in names: legacy, full or full-unambiguous
+    #[structopt(long, default_value = "legacy")]
+    pub alphabet: NameAlphabet,
+
+    /// Pack runs of this many mons as one number instead of rounding every field down to bits
+    #[structopt(long)]
+    pub mixed_radix_run: Option<usize>,
+
+    /// Store each file as its own record so adding files later only adds mons
+    #[structopt(long)]
+    pub append_log: bool,
+
+    /// Compress all the files together instead of one by one
+    #[structopt(long)]
+    pub archive: bool,
+
+    /// Guide of the PC already in game, data mons are moved around to reuse as many of its
+    /// mons as possible
+    #[structopt(long, parse(from_os_str))]
+    pub reuse_from: Option<PathBuf>,
+
+    /// Spread the data over as many PCs as it needs, written to pc-1.json, pc-2.json...
+    #[structopt(long)]
+    pub split: bool,
+
+    /// Encrypt the files, the same password is needed to decode them
+    #[structopt(long)]
+    pub password: Option<String>,
+
+    /// Named encoding profile for the layout, checksums and parity. The parity, checksum, log and
+    /// archive options are added on top of it
+    #[structopt(
+        long,
+        parse(try_from_str = parse_profile),
+        conflicts_with_all = &["alphabet", "mixed-radix-run"]
+    )]
+    pub profile: Option<&'static EncodingProfile>,
+}
+
+fn parse_profile(name: &str) -> Result<&'static EncodingProfile, String> {
+    match profile_by_name(name) {
+        // Balls, markings and natures aren't read from screenshots yet so decode couldn't get
+        // them back
+        Some(profile)
+            if profile.layout.captured_ball || profile.layout.markings || profile.layout.nature =>
+        {
+            Err(format!(
+                "The {} profile stores data in balls, markings or natures which can't be decoded yet",
+                name
+            ))
+        }
+        Some(profile) => Ok(profile),
+        None => Err(format!("Unknown profile {}", name)),
+    }
 }
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "decode-mon-fs")]
 pub struct OptionsDecode {
-    #[structopt(short, long, parse(from_os_str))]
-    pub python_script_path: PathBuf,
-
     #[structopt(short, long, parse(from_os_str))]
     pub pc_screenshots: PathBuf,
 
     #[structopt(short, long, parse(from_os_str))]
     pub decode_to: PathBuf,
+
+    /// Must match the alphabet the PC was encoded with
+    #[structopt(long, default_value = "legacy")]
+    pub alphabet: NameAlphabet,
+
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "plan-mon-fs")]
+pub struct OptionsPlan {
+    /// Guide for the PC as it is in game now
+    #[structopt(long, parse(from_os_str))]
+    pub from: PathBuf,
+
+    /// Guide for the PC it should become
+    #[structopt(long, parse(from_os_str))]
+    pub to: PathBuf,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "rm-mon-fs")]
+pub struct OptionsRemove {
+    pub name: String,
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "mv-mon-fs")]
+pub struct OptionsMove {
+    pub from: String,
+    pub to: String,
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "put-mon-fs")]
+pub struct OptionsPut {
+    #[structopt(parse(from_os_str))]
+    pub source: PathBuf,
+
+    /// Name in the PC, defaults to the source's file name
+    #[structopt(long)]
+    pub name: Option<String>,
+
+    /// Overwrite a file already in the PC with the same name
+    #[structopt(long)]
+    pub force: bool,
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "ls-mon-fs")]
+pub struct OptionsList {
+    /// Print JSON instead of a table
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "info-mon-fs")]
+pub struct OptionsInfo {
+    /// Print JSON instead of a summary
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "verify-mon-fs")]
+pub struct OptionsVerify {
+    /// Check screenshots of the PC instead of the PC file
+    #[structopt(short, long, parse(from_os_str))]
+    pub pc_screenshots: Option<PathBuf>,
+
+    /// Must match the alphabet the PC was encoded with
+    #[structopt(long, default_value = "legacy")]
+    pub alphabet: NameAlphabet,
+
+    /// Password the PC was encrypted with
+    #[structopt(long)]
+    pub password: Option<String>,
 }
 
 #[derive(StructOpt, Debug)]
 pub enum Command {
     Encode(OptionsEncode),
     Decode(OptionsDecode),
+    /// Steps to turn one guide's PC into another's
+    Plan(OptionsPlan),
+    /// Remove a file from the PC
+    Rm(OptionsRemove),
+    /// Rename a file in the PC
+    Mv(OptionsMove),
+    /// Add a single file to the PC
+    Put(OptionsPut),
+    /// List the files in the PC
+    Ls(OptionsList),
+    /// Show how much of the PC is used
+    Info(OptionsInfo),
+    /// Check every file in the PC against its hash
+    Verify(OptionsVerify),
 }
 
 #[derive(StructOpt, Debug)]
@@ -0,0 +1,134 @@
+use crate::image::GrayImage;
+
+macro_rules! font_sheet {
+    ($folder:literal, [$(($character:literal, $file:literal)),* $(,)?]) => {
+        [$((
+            $character,
+            include_bytes!(concat!(
+                "../../pc_screenshot_decoder/fonts/letters/",
+                $folder,
+                "/",
+                $file,
+                ".png"
+            )) as &[u8],
+        )),*]
+    };
+}
+
+// Glyphs cut from the emerald font sprite sheets by the python decoder.
+// Sprites past 9 are named by their index in the sheet.
+macro_rules! emerald_font {
+    ($folder:literal) => {
+        font_sheet!(
+            $folder,
+            [
+                ("0", "0"),
+                ("1", "1"),
+                ("2", "2"),
+                ("3", "3"),
+                ("4", "4"),
+                ("5", "5"),
+                ("6", "6"),
+                ("7", "7"),
+                ("8", "8"),
+                ("9", "9"),
+                ("!", "10"),
+                ("?", "11"),
+                (".", "12"),
+                ("-", "13"),
+                ("·", "14"),
+                ("…", "15"),
+                ("“", "16"),
+                ("”", "17"),
+                ("‘", "18"),
+                ("’", "19"),
+                ("♂", "20"),
+                ("♀", "21"),
+                ("$", "22"),
+                (",", "23"),
+                ("×", "24"),
+                ("/", "25"),
+                ("A", "A"),
+                ("B", "B"),
+                ("C", "C"),
+                ("D", "D"),
+                ("E", "E"),
+                ("F", "F"),
+                ("G", "G"),
+                ("H", "H"),
+                ("I", "I"),
+                ("J", "J"),
+                ("K", "K"),
+                ("L", "L"),
+                ("M", "M"),
+                ("N", "N"),
+                ("O", "O"),
+                ("P", "P"),
+                ("Q", "Q"),
+                ("R", "R"),
+                ("S", "S"),
+                ("T", "T"),
+                ("U", "U"),
+                ("V", "V"),
+                ("W", "W"),
+                ("X", "X"),
+                ("Y", "Y"),
+                ("Z", "Z"),
+                ("a", "a"),
+                ("b", "b"),
+                ("c", "c"),
+                ("d", "d"),
+                ("e", "e"),
+                ("f", "f"),
+                ("g", "g"),
+                ("h", "h"),
+                ("i", "i"),
+                ("j", "j"),
+                ("k", "k"),
+                ("l", "l"),
+                ("m", "m"),
+                ("n", "n"),
+                ("o", "o"),
+                ("p", "p"),
+                ("q", "q"),
+                ("r", "r"),
+                ("s", "s"),
+                ("t", "t"),
+                ("u", "u"),
+                ("v", "v"),
+                ("w", "w"),
+                ("x", "x"),
+                ("y", "y"),
+                ("z", "z"),
+            ]
+        )
+    };
+}
+
+pub type FontSheet = [(&'static str, &'static [u8]); 78];
+
+pub const LATIN_NORMAL: FontSheet = emerald_font!("latin_normal");
+pub const LATIN_SHORT: FontSheet = emerald_font!("latin_short");
+pub const LATIN_SMALL_NARROW: FontSheet = emerald_font!("latin_small_narrow");
+
+pub const MALE: &[u8] =
+    include_bytes!("../../pc_screenshot_decoder/fonts/letters/genders/male.png");
+pub const FEMALE: &[u8] =
+    include_bytes!("../../pc_screenshot_decoder/fonts/letters/genders/female.png");
+
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    pub character: &'static str,
+    pub image: GrayImage,
+}
+
+pub fn load_glyphs(sheet: &FontSheet, include: impl Fn(&str) -> bool) -> Vec<Glyph> {
+    sheet
+        .iter()
+        .filter(|(character, _)| include(character))
+        .map(|(character, raw)| Glyph {
+            character,
+            image: GrayImage::from_png(raw).unwrap(),
+        })
+        .collect()
+}
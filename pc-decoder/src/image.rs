@@ -0,0 +1,145 @@
+use crate::DecodeError;
+
+// Size of a GBA screen, screenshots must be this or an integer multiple of it
+pub const SCREEN_WIDTH: usize = 240;
+pub const SCREEN_HEIGHT: usize = 160;
+
+#[derive(Debug, Clone)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<f32>,
+}
+
+impl GrayImage {
+    pub fn from_png(raw: &[u8]) -> Result<GrayImage, DecodeError> {
+        let mut decoder = png::Decoder::new(raw);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = match decoder.read_info() {
+            Ok(reader) => reader,
+            Err(err) => return Err(DecodeError::BadImage(err.to_string())),
+        };
+
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = match reader.next_frame(&mut buf) {
+            Ok(info) => info,
+            Err(err) => return Err(DecodeError::BadImage(err.to_string())),
+        };
+
+        let channels = info.color_type.samples();
+        let width = info.width as usize;
+        let height = info.height as usize;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for pixel in buf[..info.buffer_size()].chunks_exact(channels) {
+            let value = match channels {
+                1 | 2 => pixel[0] as f32,
+                _ => 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32,
+            };
+            pixels.push(value);
+        }
+
+        Ok(GrayImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // Scale an upscaled emulator screenshot back down to the native GBA resolution
+    pub fn into_screen_size(self) -> Result<GrayImage, DecodeError> {
+        let scale = self.width / SCREEN_WIDTH;
+        if scale == 0 || self.width != SCREEN_WIDTH * scale || self.height != SCREEN_HEIGHT * scale
+        {
+            return Err(DecodeError::UnsupportedResolution(self.width, self.height));
+        }
+
+        if scale == 1 {
+            return Ok(self);
+        }
+
+        let mut pixels = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT);
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                pixels.push(self.get(x * scale, y * scale));
+            }
+        }
+
+        Ok(GrayImage {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            pixels,
+        })
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> GrayImage {
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in y..y + height {
+            let start = row * self.width + x;
+            pixels.extend_from_slice(&self.pixels[start..start + width]);
+        }
+
+        GrayImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Match {
+    pub x: usize,
+    pub confidence: f32,
+}
+
+// Normalised cross correlation of the template at every position in the image
+// returning every position scoring at least threshold
+pub fn match_template(image: &GrayImage, template: &GrayImage, threshold: f32) -> Vec<Match> {
+    let mut matches = Vec::new();
+    if template.width > image.width || template.height > image.height {
+        return matches;
+    }
+
+    let template_mean = template.pixels.iter().sum::<f32>() / template.pixels.len() as f32;
+    let template_centered: Vec<f32> = template.pixels.iter().map(|p| p - template_mean).collect();
+    let template_norm: f32 = template_centered.iter().map(|p| p * p).sum();
+    if template_norm == 0.0 {
+        return matches;
+    }
+
+    let area = (template.width * template.height) as f32;
+    for y in 0..=image.height - template.height {
+        for x in 0..=image.width - template.width {
+            let mut sum = 0.0;
+            let mut sum_squared = 0.0;
+            let mut cross = 0.0;
+            for ty in 0..template.height {
+                let row = (y + ty) * image.width + x;
+                let template_row = ty * template.width;
+                for tx in 0..template.width {
+                    let value = image.pixels[row + tx];
+                    sum += value;
+                    sum_squared += value * value;
+                    cross += value * template_centered[template_row + tx];
+                }
+            }
+
+            let image_norm = sum_squared - sum * sum / area;
+            if image_norm <= f32::EPSILON {
+                continue;
+            }
+
+            let confidence = cross / (image_norm * template_norm).sqrt();
+            if confidence >= threshold {
+                matches.push(Match { x, confidence });
+            }
+        }
+    }
+
+    matches
+}
@@ -0,0 +1,316 @@
+use std::path::Path;
+
+use font::{load_glyphs, Glyph};
+use image::{match_template, GrayImage, SCREEN_WIDTH};
+use mon_fs_box::{
+    box_mon::StringsMon,
+    mon_field::FromStringInput,
+    mon_name::{BoxMonCharacter, NameAlphabet},
+};
+
+mod font;
+mod image;
+
+const PC_BOX_SIZE: usize = 30;
+const NAME_LENGTH: usize = 10;
+const MATCH_THRESHOLD: f32 = 0.9;
+
+// Only the mon info panel on the left of the PC screen is read
+const PANEL_WIDTH: usize = SCREEN_WIDTH / 3;
+
+// Rows of the info panel holding each field (start, end)
+const NAME_ROWS: (usize, usize) = (84, 103);
+// Column the first name character is drawn at and the width of a space
+const NAME_START: usize = 6;
+const SPACE_WIDTH: usize = 6;
+const SPECIES_ROWS: (usize, usize) = (103, 117);
+const GENDER_ROWS: (usize, usize) = (115, 134);
+const ITEM_ROWS: (usize, usize) = (132, 146);
+
+#[derive(Debug)]
+pub enum DecodeError {
+    BadImage(String),
+    UnsupportedResolution(usize, usize),
+    BadName(String),
+    BadSpecies(String),
+    MissingGender,
+    InScreenshot(usize, Box<DecodeError>),
+    IoError(std::io::Error),
+}
+
+pub struct Decoder {
+    alphabet: NameAlphabet,
+    name_glyphs: Vec<Glyph>,
+    species_glyphs: Vec<Glyph>,
+    item_glyphs: Vec<Glyph>,
+    male: GrayImage,
+    female: GrayImage,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Decoder {
+        Self::with_alphabet(NameAlphabet::Legacy)
+    }
+
+    // Only glyphs in the alphabet the PC was encoded with are matched in names
+    pub fn with_alphabet(alphabet: NameAlphabet) -> Decoder {
+        Decoder {
+            alphabet,
+            name_glyphs: load_glyphs(&font::LATIN_NORMAL, |c| {
+                BoxMonCharacter::try_from_string(c).is_some_and(|c| alphabet.contains(c))
+            }),
+            species_glyphs: load_glyphs(&font::LATIN_SHORT, |c| {
+                c.chars().all(|c| c.is_ascii_uppercase())
+            }),
+            item_glyphs: load_glyphs(&font::LATIN_SMALL_NARROW, |c| {
+                c == "." || c.chars().all(|c| c.is_ascii_uppercase())
+            }),
+            male: GrayImage::from_png(font::MALE).unwrap(),
+            female: GrayImage::from_png(font::FEMALE).unwrap(),
+        }
+    }
+
+    pub fn decode_screenshot(&self, png: &[u8]) -> Result<StringsMon, DecodeError> {
+        let screen = GrayImage::from_png(png)?.into_screen_size()?;
+
+        let name = self.read_name(&panel_rows(&screen, NAME_ROWS));
+        if name.chars().count() != NAME_LENGTH {
+            return Err(DecodeError::BadName(name));
+        }
+
+        let species = read_text(&panel_rows(&screen, SPECIES_ROWS), &self.species_glyphs);
+        if mon_fs_box::mon_species::BoxMonSpecies::try_from_string(&species).is_none() {
+            return Err(DecodeError::BadSpecies(species));
+        }
+
+        let gender_rows = panel_rows(&screen, GENDER_ROWS);
+        let gender = if !match_template(&gender_rows, &self.male, MATCH_THRESHOLD).is_empty() {
+            "M"
+        } else if !match_template(&gender_rows, &self.female, MATCH_THRESHOLD).is_empty() {
+            "F"
+        } else {
+            return Err(DecodeError::MissingGender);
+        };
+
+        let item = read_text(&panel_rows(&screen, ITEM_ROWS), &self.item_glyphs);
+
+        Ok(StringsMon {
+            name,
+            species,
+            gender: gender.to_string(),
+            item,
+            // The ball and nature aren't shown on the PC screen, markings aren't read yet
+            ball: String::new(),
+            markings: None,
+            nature: None,
+        })
+    }
+
+    // Spaces aren't drawn so they are filled in from the gaps between glyphs
+    fn read_name(&self, region: &GrayImage) -> String {
+        let glyphs = read_glyphs(region, &self.name_glyphs);
+        if !self.alphabet.contains(BoxMonCharacter::Space) {
+            return glyphs.iter().map(|(_, _, glyph)| glyph.character).collect();
+        }
+
+        let mut name = String::new();
+        let mut cursor = NAME_START;
+        for (start, end, glyph) in glyphs {
+            while start >= cursor + SPACE_WIDTH {
+                name.push(' ');
+                cursor += SPACE_WIDTH;
+            }
+            name.push_str(glyph.character);
+            cursor = end;
+        }
+        while name.chars().count() < NAME_LENGTH {
+            name.push(' ');
+        }
+
+        name
+    }
+
+    // Screenshots must be in PC order, every 30 starts a new box
+    pub fn decode_screenshots<'a>(
+        &self,
+        pngs: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<Vec<Vec<StringsMon>>, DecodeError> {
+        let mut boxes: Vec<Vec<StringsMon>> = vec![vec![]];
+        for (i, png) in pngs.into_iter().enumerate() {
+            let mon = match self.decode_screenshot(png) {
+                Ok(mon) => mon,
+                Err(err) => return Err(DecodeError::InScreenshot(i, Box::new(err))),
+            };
+
+            if boxes.last().unwrap().len() >= PC_BOX_SIZE {
+                boxes.push(vec![]);
+            }
+            boxes.last_mut().unwrap().push(mon);
+        }
+
+        Ok(boxes)
+    }
+}
+
+// Decodes every png in the folder ordered by a natural sort of the file names
+pub fn decode_folder(folder: &Path) -> Result<Vec<Vec<StringsMon>>, DecodeError> {
+    decode_folder_with_alphabet(folder, NameAlphabet::Legacy)
+}
+
+pub fn decode_folder_with_alphabet(
+    folder: &Path,
+    alphabet: NameAlphabet,
+) -> Result<Vec<Vec<StringsMon>>, DecodeError> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(err) => return Err(DecodeError::IoError(err)),
+    };
+
+    let mut paths = vec![];
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => return Err(DecodeError::IoError(err)),
+        };
+        if path.extension().is_some_and(|ext| ext == "png") {
+            paths.push(path);
+        }
+    }
+    paths.sort_by_key(|path| natural_key(&path.file_name().unwrap().to_string_lossy()));
+
+    let mut screenshots = vec![];
+    for path in paths {
+        match std::fs::read(path) {
+            Ok(raw) => screenshots.push(raw),
+            Err(err) => return Err(DecodeError::IoError(err)),
+        }
+    }
+
+    Decoder::with_alphabet(alphabet).decode_screenshots(screenshots.iter().map(|s| s.as_slice()))
+}
+
+fn panel_rows(screen: &GrayImage, rows: (usize, usize)) -> GrayImage {
+    screen.crop(0, rows.0, PANEL_WIDTH, rows.1 - rows.0)
+}
+
+fn read_text(region: &GrayImage, glyphs: &[Glyph]) -> String {
+    read_glyphs(region, glyphs)
+        .iter()
+        .map(|(_, _, glyph)| glyph.character)
+        .collect()
+}
+
+// Matched glyphs left to right as (start, end, glyph)
+fn read_glyphs<'a>(region: &GrayImage, glyphs: &'a [Glyph]) -> Vec<(usize, usize, &'a Glyph)> {
+    let mut candidates = vec![];
+    for glyph in glyphs {
+        for found in match_template(region, &glyph.image, MATCH_THRESHOLD) {
+            candidates.push((found, glyph));
+        }
+    }
+
+    // Keep the most confident glyph where matches overlap
+    candidates.sort_by(|a, b| b.0.confidence.total_cmp(&a.0.confidence));
+    let mut accepted: Vec<(usize, usize, &Glyph)> = vec![];
+    for (found, glyph) in candidates {
+        let start = found.x;
+        let end = found.x + glyph.image.width;
+        let overlaps = accepted.iter().any(|(other_start, other_end, _)| {
+            let overlap = end.min(*other_end).saturating_sub(start.max(*other_start));
+            overlap * 2 > (end - start).min(other_end - other_start)
+        });
+        if !overlaps {
+            accepted.push((start, end, glyph));
+        }
+    }
+
+    accepted.sort_by_key(|(start, _, _)| *start);
+    accepted
+}
+
+// Splits digits out of a file name so "mon-10" sorts after "mon-9"
+fn natural_key(name: &str) -> Vec<(String, u64)> {
+    let mut key = vec![];
+    let mut text = String::new();
+    let mut number = None;
+    for c in name.chars() {
+        match (c.to_digit(10), number) {
+            (Some(digit), _) => number = Some(number.unwrap_or(0) * 10 + digit as u64),
+            (None, Some(value)) => {
+                key.push((std::mem::take(&mut text), value));
+                number = None;
+                text.push(c);
+            }
+            (None, None) => text.push(c),
+        }
+    }
+    key.push((text, number.unwrap_or(0)));
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EMU_FOLDER: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../pc_screenshot_decoder/example/emu"
+    );
+
+    #[test]
+    fn decode_emu_screenshot() {
+        let raw = include_bytes!("../../pc_screenshot_decoder/example/emu/pokeemerald-3.png");
+        let mon = Decoder::new().decode_screenshot(raw).unwrap();
+
+        assert_eq!(mon.name, "YYoWx9uFx3");
+        assert_eq!(mon.species, "NINCADA");
+        assert_eq!(mon.gender, "M");
+        assert_eq!(mon.item, "FULLRESTORE");
+    }
+
+    #[test]
+    fn full_alphabet_fills_in_spaces() {
+        let raw = include_bytes!("../../pc_screenshot_decoder/example/emu/pokeemerald-3.png");
+        let decoder = Decoder::with_alphabet(NameAlphabet::Full);
+        assert_eq!(decoder.decode_screenshot(raw).unwrap().name, "YYoWx9uFx3");
+
+        // Blank out the "x9" in the middle of the name
+        let screen = GrayImage::from_png(raw)
+            .unwrap()
+            .into_screen_size()
+            .unwrap();
+        let mut name = panel_rows(&screen, NAME_ROWS);
+        for y in 0..name.height {
+            for x in 30..42 {
+                name.pixels[y * name.width + x] = name.get(0, 0);
+            }
+        }
+        assert_eq!(decoder.read_name(&name), "YYoW  uFx3");
+    }
+
+    #[test]
+    fn decode_emu_folder() {
+        let boxes = decode_folder(Path::new(EMU_FOLDER)).unwrap();
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(boxes[0].len(), 11);
+        assert_eq!(boxes[0][0].name, "Daaaaaaaaa");
+        assert_eq!(boxes[0][0].species, "POOCHYENA");
+        assert_eq!(boxes[0][4].name, "pRh/vBrJaa");
+        assert_eq!(boxes[0][4].gender, "F");
+        assert_eq!(boxes[0][9].name, "X2aNy?jSyZ");
+        assert_eq!(boxes[0][10].species, "WHISMUR");
+    }
+
+    #[test]
+    fn natural_sort_order() {
+        let mut names = vec!["mon-10.png", "mon-9.png", "mon-1.png"];
+        names.sort_by_key(|name| natural_key(name));
+        assert_eq!(names, vec!["mon-1.png", "mon-9.png", "mon-10.png"]);
+    }
+}
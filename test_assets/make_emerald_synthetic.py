@@ -0,0 +1,160 @@
+# Builds emerald_synthetic.sav, the save the tests in box/src/save.rs read.
+#   python3 test_assets/make_emerald_synthetic.py
+#
+# Written separately from save.rs so the fixture checks the Rust code instead of agreeing
+# with it. Nothing here comes from a real game, every value is made up:
+#   - Trainer ID 0x0BADF00D, trainer name "ABC"
+#   - Slot A, save index 41: only the mon in box 1 slot 1
+#   - Slot B, save index 42: four mons, sections rotated by 5 like the game does
+#   - Box names "BOX1" to "BOX14", the rest of the save is zeros
+import struct
+from pathlib import Path
+
+SAVE_SIZE = 0x20000
+SECTION_SIZE = 0x1000
+SECTIONS_PER_SLOT = 14
+# Bytes of each section covered by its checksum, indexed by section ID
+SECTION_DATA_SIZES = [3884, 3968, 3968, 3968, 3848] + [3968] * 8 + [2000]
+SECTION_SIGNATURE = 0x08012025
+
+# The PC buffer is spread over sections 5 to 13
+PC_BUFFER_FIRST_SECTION = 5
+PC_BUFFER_SIZE = 33744
+PC_MONS_OFFSET = 4
+BOX_MON_SIZE = 80
+NUM_OF_MONS = 420
+BOX_NAME_SIZE = 9
+
+STRING_TERMINATOR = 0xFF
+LANGUAGE_ENGLISH = 2
+FLAG_HAS_SPECIES = 0x02
+OT_NAME = b"\xBB\xBC\xBD\xFF\xFF\xFF\xFF"
+
+
+def character_map():
+    characters = {}
+    for i, character in enumerate("ABCDEFGHIJKLMNOPQRSTUVWXYZ"):
+        characters[character] = 0xBB + i
+    for i, character in enumerate("abcdefghijklmnopqrstuvwxyz"):
+        characters[character] = 0xD5 + i
+    for i, character in enumerate("0123456789"):
+        characters[character] = 0xA1 + i
+    characters.update({
+        " ": 0x00, "!": 0xAB, "?": 0xAC, ".": 0xAD, "-": 0xAE, "…": 0xB0,
+        "♂": 0xB5, "♀": 0xB6, ",": 0xB8, "/": 0xBA,
+    })
+    return characters
+
+
+CHARACTERS = character_map()
+
+# Substructure order for personality % 24: Growth, Attacks, Evs, Misc
+SUBSTRUCT_ORDERS = [
+    "GAEM", "GAME", "GEAM", "GEMA", "GMAE", "GMEA", "AGEM", "AGME", "AEGM", "AEMG", "AMGE", "AMEG",
+    "EGAM", "EGMA", "EAGM", "EAMG", "EMGA", "EMAG", "MGAE", "MGEA", "MAGE", "MAEG", "MEGA", "MEAG",
+]
+
+SPECIES = {"POOCHYENA": 286, "NINCADA": 301, "WHISMUR": 370, "TAILLOW": 304}
+ITEMS = {"": 0, "Potion": 13, "Timer Ball": 10, "Nest Ball": 8}
+BALLS = {"Poké Ball": 4, "Nest Ball": 8, "Repeat Ball": 9}
+
+
+def box_mon(personality, ot_id, name, species, item, ball):
+    nickname = bytes(CHARACTERS[character] for character in name)
+    assert len(nickname) == 10
+
+    # Experience 135 and friendship 70
+    growth = struct.pack("<HHIBBH", SPECIES[species], ITEMS[item], 135, 0, 70, 0)
+    # Tackle with 35 PP
+    attacks = struct.pack("<HHHHBBBB", 33, 0, 0, 0, 35, 0, 0, 0)
+    evs = bytes(12)
+    # Met at level 5 in Emerald
+    origins = 5 | (3 << 7) | (BALLS[ball] << 11)
+    # Met on route 116
+    misc = struct.pack("<BBHII", 0, 0x1F, origins, 0, 0)
+
+    substructs = {"G": growth, "A": attacks, "E": evs, "M": misc}
+    secure = b"".join(substructs[s] for s in SUBSTRUCT_ORDERS[personality % 24])
+    checksum = sum(struct.unpack("<24H", secure)) & 0xFFFF
+    key = personality ^ ot_id
+    encrypted = b"".join(struct.pack("<I", word ^ key) for word in struct.unpack("<12I", secure))
+
+    header = (
+        struct.pack("<II", personality, ot_id)
+        + nickname
+        + bytes([LANGUAGE_ENGLISH, FLAG_HAS_SPECIES])
+        + OT_NAME
+        + bytes([0])
+        + struct.pack("<HH", checksum, 0)
+    )
+    mon = header + encrypted
+    assert len(mon) == BOX_MON_SIZE
+    return mon
+
+
+def section_checksum(data):
+    total = 0
+    for (word,) in struct.iter_unpack("<I", data):
+        total = (total + word) & 0xFFFFFFFF
+    return ((total >> 16) + total) & 0xFFFF
+
+
+def build_slot(save_index, rotation, mons, trainer_id):
+    trainer_info = bytearray(SECTION_DATA_SIZES[0])
+    trainer_info[0:7] = OT_NAME
+    trainer_info[0x0A:0x0E] = struct.pack("<I", trainer_id)
+
+    pc_buffer = bytearray(PC_BUFFER_SIZE)
+    for (box_index, slot), raw in mons.items():
+        offset = PC_MONS_OFFSET + (box_index * 30 + slot) * BOX_MON_SIZE
+        pc_buffer[offset:offset + BOX_MON_SIZE] = raw
+    for box_index in range(14):
+        offset = PC_MONS_OFFSET + NUM_OF_MONS * BOX_MON_SIZE + box_index * BOX_NAME_SIZE
+        name = bytes(CHARACTERS[character] for character in f"BOX{box_index + 1}")
+        padding = bytes([STRING_TERMINATOR] * (BOX_NAME_SIZE - len(name)))
+        pc_buffer[offset:offset + BOX_NAME_SIZE] = name + padding
+
+    sections = {0: bytes(trainer_info)}
+    for section_id in range(1, PC_BUFFER_FIRST_SECTION):
+        sections[section_id] = bytes(SECTION_DATA_SIZES[section_id])
+    offset = 0
+    for section_id in range(PC_BUFFER_FIRST_SECTION, SECTIONS_PER_SLOT):
+        size = SECTION_DATA_SIZES[section_id]
+        sections[section_id] = bytes(pc_buffer[offset:offset + size])
+        offset += size
+
+    slot = bytearray()
+    for position in range(SECTIONS_PER_SLOT):
+        section_id = (position + rotation) % SECTIONS_PER_SLOT
+        data = sections[section_id]
+        section = bytearray(SECTION_SIZE)
+        section[:len(data)] = data
+        section[0xFF4:0xFF6] = struct.pack("<H", section_id)
+        section[0xFF6:0xFF8] = struct.pack("<H", section_checksum(data))
+        section[0xFF8:0xFFC] = struct.pack("<I", SECTION_SIGNATURE)
+        section[0xFFC:0x1000] = struct.pack("<I", save_index)
+        slot += section
+    return slot
+
+
+def main():
+    trainer_id = 0x0BADF00D
+    mons = {
+        (0, 0): box_mon(0x12345680, trainer_id, "aaaaaaaaaa", "POOCHYENA", "", "Poké Ball"),
+        (0, 1): box_mon(0x0000BE10, trainer_id, "Heyyo?…♂/2", "NINCADA", "Potion", "Poké Ball"),
+        (0, 2): box_mon(
+            0xCAFE00FF, trainer_id, "ABCDEFGHIJ", "WHISMUR", "Timer Ball", "Repeat Ball"
+        ),
+        (1, 5): box_mon(0x00000007, trainer_id, "zzzzzzzzzz", "TAILLOW", "Nest Ball", "Nest Ball"),
+    }
+
+    older = build_slot(41, 0, {(0, 0): mons[(0, 0)]}, trainer_id)
+    newer = build_slot(42, 5, mons, trainer_id)
+    save = older + newer + bytes(SAVE_SIZE - len(older) - len(newer))
+    assert len(save) == SAVE_SIZE
+
+    (Path(__file__).parent / "emerald_synthetic.sav").write_bytes(save)
+
+
+if __name__ == "__main__":
+    main()
@@ -1,15 +1,20 @@
 use std::io::Write;
 
-use mon_fs_box::{file_pc::FilePc, pc::PC};
+use mon_fs_box::{
+    error::MonFsError,
+    file_pc::{FilePc, PcEncodeOptions, MODE_MASK},
+    pc::PC,
+    storage::Storage,
+};
 use wasm_bindgen::prelude::*;
 use zip::{write::SimpleFileOptions, ZipWriter};
 
 #[wasm_bindgen]
 pub fn encode_file(existing_pc: String, filename: String, to_encode: Vec<u8>) -> String {
-    let mut pc: PC = serde_json::from_str(&existing_pc).unwrap();
-    pc.fill_empty_mon_slots();
+    let mut storage: Storage = serde_json::from_str(&existing_pc).unwrap();
+    storage.pc.fill_empty_mon_slots();
 
-    let mut file_pc = if let Some(file_pc) = FilePc::new_from_pc(pc) {
+    let mut file_pc = if let Ok(file_pc) = FilePc::new_from_storage(storage) {
         file_pc
     } else {
         FilePc::new()
@@ -17,19 +22,176 @@ pub fn encode_file(existing_pc: String, filename: String, to_encode: Vec<u8>) ->
 
     file_pc.add_file_raw(&filename, to_encode).unwrap();
 
-    let pc: PC = file_pc.into();
+    let pc: PC = file_pc.try_into().unwrap();
 
     serde_json::to_string(&pc).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn encode_file_with_password(
+    existing_pc: String,
+    filename: String,
+    to_encode: Vec<u8>,
+    password: String,
+) -> Result<String, JsValue> {
+    add_file_to_json(&existing_pc, Some(&password), &filename, to_encode).map_err(to_js_error)
+}
+
+// Edits keep the options the PC was written with
+fn file_pc_from_json(
+    existing_pc: &str,
+    password: Option<&str>,
+) -> Result<(FilePc, PcEncodeOptions), MonFsError> {
+    let mut storage: Storage = serde_json::from_str(existing_pc).unwrap();
+    storage.pc.fill_empty_mon_slots();
+
+    let mut options = PcEncodeOptions::from_pc(&storage.pc)?;
+    options.password = password.map(str::to_string);
+    let (file_pc, _) = FilePc::new_from_storage_with_password(storage, password)?;
+
+    Ok((file_pc, options))
+}
+
+fn file_pc_to_json(file_pc: FilePc, options: &PcEncodeOptions) -> Result<String, MonFsError> {
+    let pc = file_pc.as_pc_with_options(options)?;
+
+    Ok(serde_json::to_string(&pc).unwrap())
+}
+
+fn edit_json(
+    existing_pc: &str,
+    password: Option<&str>,
+    edit: impl FnOnce(&mut FilePc) -> Result<(), MonFsError>,
+) -> Result<String, MonFsError> {
+    let (mut file_pc, options) = file_pc_from_json(existing_pc, password)?;
+    edit(&mut file_pc)?;
+
+    file_pc_to_json(file_pc, &options)
+}
+
+fn remove_file_from_json(
+    existing_pc: &str,
+    password: Option<&str>,
+    filename: &str,
+) -> Result<String, MonFsError> {
+    edit_json(existing_pc, password, |file_pc| {
+        file_pc.remove_file(filename).map(drop)
+    })
+}
+
+fn rename_file_in_json(
+    existing_pc: &str,
+    password: Option<&str>,
+    from: &str,
+    to: &str,
+) -> Result<String, MonFsError> {
+    edit_json(existing_pc, password, |file_pc| {
+        file_pc.rename_file(from, to)
+    })
+}
+
+fn replace_file_in_json(
+    existing_pc: &str,
+    password: Option<&str>,
+    filename: &str,
+    to_encode: Vec<u8>,
+) -> Result<String, MonFsError> {
+    edit_json(existing_pc, password, |file_pc| {
+        file_pc.replace_file(filename, to_encode)
+    })
+}
+
+// A PC without any mons starts a new file PC, anything else has to decode
+fn add_file_to_json(
+    existing_pc: &str,
+    password: Option<&str>,
+    filename: &str,
+    to_encode: Vec<u8>,
+) -> Result<String, MonFsError> {
+    let storage: Storage = serde_json::from_str(existing_pc).unwrap();
+    if storage.pc.mons.iter().all(Option::is_none) {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw(filename, to_encode)?;
+        let options = PcEncodeOptions {
+            password: password.map(str::to_string),
+            ..Default::default()
+        };
+        return file_pc_to_json(file_pc, &options);
+    }
+
+    edit_json(existing_pc, password, |file_pc| {
+        file_pc.add_file_raw(filename, to_encode)
+    })
+}
+
+fn to_js_error(err: MonFsError) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+#[wasm_bindgen]
+pub fn remove_file(existing_pc: String, filename: String) -> String {
+    remove_file_from_json(&existing_pc, None, &filename).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn remove_file_with_password(
+    existing_pc: String,
+    filename: String,
+    password: String,
+) -> Result<String, JsValue> {
+    remove_file_from_json(&existing_pc, Some(&password), &filename).map_err(to_js_error)
+}
+
+#[wasm_bindgen]
+pub fn rename_file(existing_pc: String, from: String, to: String) -> String {
+    rename_file_in_json(&existing_pc, None, &from, &to).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn rename_file_with_password(
+    existing_pc: String,
+    from: String,
+    to: String,
+    password: String,
+) -> Result<String, JsValue> {
+    rename_file_in_json(&existing_pc, Some(&password), &from, &to).map_err(to_js_error)
+}
+
+#[wasm_bindgen]
+pub fn replace_file(existing_pc: String, filename: String, to_encode: Vec<u8>) -> String {
+    replace_file_in_json(&existing_pc, None, &filename, to_encode).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn replace_file_with_password(
+    existing_pc: String,
+    filename: String,
+    to_encode: Vec<u8>,
+    password: String,
+) -> Result<String, JsValue> {
+    replace_file_in_json(&existing_pc, Some(&password), &filename, to_encode).map_err(to_js_error)
+}
+
 #[wasm_bindgen]
 pub fn decode_file(existing_pc: String) -> Vec<u8> {
-    let mut pc: PC = serde_json::from_str(&existing_pc).unwrap();
+    decode_json(&existing_pc, None).unwrap()
+}
 
-    pc.fill_empty_mon_slots();
+#[wasm_bindgen]
+pub fn decode_file_with_password(
+    existing_pc: String,
+    password: String,
+) -> Result<Vec<u8>, JsValue> {
+    decode_json(&existing_pc, Some(&password)).map_err(to_js_error)
+}
+
+fn decode_json(existing_pc: &str, password: Option<&str>) -> Result<Vec<u8>, MonFsError> {
+    let (file_pc, _) = file_pc_from_json(existing_pc, password)?;
 
-    let file_pc = FilePc::new_from_pc(pc).unwrap();
+    zip_files(&file_pc)
+}
 
+fn zip_files(file_pc: &FilePc) -> Result<Vec<u8>, MonFsError> {
     let mut result = Vec::new();
     let mut cursor = std::io::Cursor::new(&mut result);
     let mut zip = ZipWriter::new(&mut cursor);
@@ -37,14 +199,36 @@ pub fn decode_file(existing_pc: String) -> Vec<u8> {
     let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
 
     for file in &file_pc.files {
+        let options = match file.mode {
+            Some(mode) => options.unix_permissions(mode & MODE_MASK),
+            None => options,
+        };
         zip.start_file(file.name.to_string(), options).unwrap();
 
-        zip.write_all(&file.get_data()).unwrap();
+        zip.write_all(&file.get_data()?).unwrap();
     }
 
     zip.finish().unwrap();
 
-    result
+    Ok(result)
+}
+
+// Returns the slots that failed their checksum as JSON
+#[wasm_bindgen]
+pub fn verify_pc(existing_pc: String) -> String {
+    let mut pc: PC = serde_json::from_str(&existing_pc).unwrap();
+    pc.fill_empty_mon_slots();
+
+    serde_json::to_string(&pc.verify().unwrap()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn decode_screenshot(screenshot: Vec<u8>) -> String {
+    let mon = mon_fs_pc_decoder::Decoder::new()
+        .decode_screenshot(&screenshot)
+        .unwrap();
+
+    serde_json::to_string(&mon).unwrap()
 }
 
 #[cfg(test)]
@@ -59,13 +243,13 @@ mod test {
         const FILE_COUNT: usize = 8;
 
         let mut data_chunk = vec![0; PC::byte_count() / 10];
-        for i in 0..data_chunk.len() {
-            data_chunk[i] = rand::thread_rng().gen()
+        for byte in data_chunk.iter_mut() {
+            *byte = rand::thread_rng().gen()
         }
 
         let file_pc = FilePc::new();
 
-        let pc: PC = file_pc.into();
+        let pc: PC = file_pc.try_into().unwrap();
 
         let mut pc_json = serde_json::to_string(&pc).unwrap();
 
@@ -90,11 +274,131 @@ mod test {
     fn fail_encode_duplicated_file() {
         let data = vec![0; 10];
         let file_pc = FilePc::new();
-        let pc: PC = file_pc.into();
+        let pc: PC = file_pc.try_into().unwrap();
         let mut pc_json = serde_json::to_string(&pc).unwrap();
 
         pc_json = encode_file(pc_json, "test.txt".to_string(), data.clone());
 
         encode_file(pc_json, "test.txt".to_string(), data.clone());
     }
+
+    #[test]
+    fn remove_rename_and_replace_in_json() {
+        let pc: PC = FilePc::new().try_into().unwrap();
+        let mut pc_json = serde_json::to_string(&pc).unwrap();
+        pc_json = encode_file(pc_json, "a.txt".to_string(), vec![1; 20]);
+        pc_json = encode_file(pc_json, "b.txt".to_string(), vec![2; 20]);
+
+        pc_json = remove_file(pc_json, "a.txt".to_string());
+        pc_json = rename_file(pc_json, "b.txt".to_string(), "c.txt".to_string());
+        pc_json = replace_file(pc_json, "c.txt".to_string(), vec![3; 20]);
+
+        let (file_pc, _) = file_pc_from_json(&pc_json, None).unwrap();
+        assert_eq!(file_pc.files.len(), 1);
+        assert_eq!(file_pc.files[0].name, "c.txt");
+        assert_eq!(file_pc.files[0].get_data().unwrap(), vec![3; 20]);
+    }
+
+    #[test]
+    fn edits_keep_the_pc_options() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("a.txt", vec![1; 200]).unwrap();
+        file_pc.add_file_raw("b.txt", vec![2; 200]).unwrap();
+        let options = PcEncodeOptions {
+            append_log: true,
+            password: Some("hunter2".to_string()),
+            ..PcEncodeOptions::from(&mon_fs_box::profile::PROFILES[1])
+        };
+        let pc = file_pc.as_pc_with_options(&options).unwrap();
+        let pc_json = serde_json::to_string(&pc).unwrap();
+
+        let password = Some("hunter2");
+        let edited = [
+            remove_file_from_json(&pc_json, password, "a.txt").unwrap(),
+            rename_file_in_json(&pc_json, password, "a.txt", "c.txt").unwrap(),
+            replace_file_in_json(&pc_json, password, "a.txt", vec![3; 200]).unwrap(),
+        ];
+        for edited in edited {
+            let (_, read_back) = file_pc_from_json(&edited, password).unwrap();
+            assert_eq!(read_back.profile, options.profile);
+            assert_eq!(read_back.parity_mons, options.parity_mons);
+            assert!(read_back.mon_checksums && read_back.append_log);
+
+            let edited: PC = serde_json::from_str(&edited).unwrap();
+            assert_eq!(edited.get_flags().unwrap(), pc.get_flags().unwrap());
+        }
+
+        assert!(matches!(
+            remove_file_from_json(&pc_json, None, "a.txt"),
+            Err(MonFsError::PasswordNeeded)
+        ));
+    }
+
+    #[test]
+    fn encode_and_decode_with_password() {
+        let pc: PC = FilePc::new().try_into().unwrap();
+        let mut pc_json = serde_json::to_string(&pc).unwrap();
+        for name in ["a.txt", "b.txt"] {
+            pc_json = add_file_to_json(&pc_json, Some("hunter2"), name, vec![7; 20]).unwrap();
+        }
+
+        let zip = decode_json(&pc_json, Some("hunter2")).unwrap();
+        let archive = zip::ZipArchive::new(std::io::Cursor::new(zip)).unwrap();
+        assert_eq!(archive.len(), 2);
+
+        assert!(matches!(
+            decode_json(&pc_json, Some("hunter3")),
+            Err(MonFsError::WrongPassword)
+        ));
+        assert!(matches!(
+            decode_json(&pc_json, None),
+            Err(MonFsError::PasswordNeeded)
+        ));
+        assert!(matches!(
+            add_file_to_json(&pc_json, Some("hunter3"), "c.txt", vec![7; 20]),
+            Err(MonFsError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn encoding_into_an_empty_pc_starts_a_new_one() {
+        let pc_json = serde_json::to_string(&PC::new()).unwrap();
+        let pc_json = add_file_to_json(&pc_json, Some("hunter2"), "a.txt", vec![7; 20]).unwrap();
+
+        let (file_pc, _) = file_pc_from_json(&pc_json, Some("hunter2")).unwrap();
+        assert_eq!(file_pc.files.len(), 1);
+        assert!(matches!(
+            file_pc_from_json(&pc_json, None),
+            Err(MonFsError::PasswordNeeded)
+        ));
+    }
+
+    #[test]
+    fn verify_pc_reports_suspect_slots() {
+        let mut file_pc = FilePc::new();
+        file_pc.add_file_raw("test.txt", vec![7; 200]).unwrap();
+        let options = mon_fs_box::file_pc::PcEncodeOptions {
+            mon_checksums: true,
+            ..Default::default()
+        };
+        let mut pc = file_pc.as_pc_with_options(&options).unwrap();
+        pc.mons.swap(1, 2);
+
+        let report: mon_fs_box::pc::IntegrityReport =
+            serde_json::from_str(&verify_pc(serde_json::to_string(&pc).unwrap())).unwrap();
+        assert_eq!(report.suspect.len(), 2);
+        assert_eq!(report.suspect[0].slot, 1);
+    }
+
+    #[test]
+    fn decode_screenshot_to_json() {
+        let screenshot =
+            include_bytes!("../../pc_screenshot_decoder/example/emu/pokeemerald-4.png").to_vec();
+
+        let mon: mon_fs_box::box_mon::StringsMon =
+            serde_json::from_str(&decode_screenshot(screenshot)).unwrap();
+
+        assert_eq!(mon.name, "pRh/vBrJaa");
+        assert_eq!(mon.species, "WHISMUR");
+    }
 }